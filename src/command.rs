@@ -0,0 +1,113 @@
+use std::io::{self, Read};
+use std::os::unix::process::CommandExt;
+use std::process::{Child, Command, Output, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Read a pipe to the end in a separate thread so that a command filling the pipe cannot block the timeout.
+fn read_pipe(mut pipe: impl Read + Send + 'static) -> Receiver<io::Result<Vec<u8>>> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let mut output = Vec::new();
+        let result = pipe.read_to_end(&mut output).map(|_| output);
+        sender.send(result)
+    });
+    receiver
+}
+
+/// Kill the process group the child leads, so that processes it started are killed along with it.
+fn kill_group(child: &mut Child) {
+    // SAFETY: `kill` has no memory safety requirements. The child has not been waited on so its pid, which is also
+    // the id of its process group, cannot have been reused.
+    unsafe {
+        libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL);
+    }
+    let _ = child.wait();
+}
+
+/// Run a command in its own process group, collecting its output as `Command::output` does.
+///
+/// Returns `None` if the command has not exited and closed its output within `timeout`, in which case the
+/// whole process group is killed.
+pub fn output_with_timeout(command: &mut Command, timeout: Duration) -> io::Result<Option<Output>> {
+    let deadline = Instant::now() + timeout;
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .process_group(0)
+        .spawn()?;
+    let stdout = read_pipe(child.stdout.take().expect("stdout is piped"));
+    let stderr = read_pipe(child.stderr.take().expect("stderr is piped"));
+
+    let read = |receiver: Receiver<io::Result<Vec<u8>>>| {
+        receiver.recv_timeout(deadline.saturating_duration_since(Instant::now()))
+    };
+    let (stdout, stderr) = match (read(stdout), read(stderr)) {
+        (Ok(stdout), Ok(stderr)) => (stdout?, stderr?),
+        _ => {
+            kill_group(&mut child);
+            return Ok(None);
+        }
+    };
+
+    // The command may close its output before exiting, so waiting for it is bound by the same deadline.
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some(Output {
+                status,
+                stdout,
+                stderr,
+            }));
+        }
+        if Instant::now() >= deadline {
+            kill_group(&mut child);
+            return Ok(None);
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::command;
+    use std::fs;
+    use std::process::{self, Command};
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    fn run(script: &str, timeout_ms: u64) -> Option<String> {
+        command::output_with_timeout(
+            Command::new("sh").arg("-c").arg(script),
+            Duration::from_millis(timeout_ms),
+        )
+        .unwrap()
+        .map(|output| String::from_utf8_lossy(&output.stdout).to_string())
+    }
+
+    #[test]
+    fn test_output_with_timeout() {
+        assert_eq!(run("echo done", 5000), Some("done\n".to_string()));
+
+        let start = Instant::now();
+        // Still running after closing its output.
+        assert_eq!(run("exec >&- 2>&-; sleep 5", 100), None);
+        assert!(start.elapsed() < Duration::from_secs(2));
+
+        // Processes started by the command are killed along with it.
+        let pid_path = std::env::temp_dir().join(format!("sysstatus-command-{}", process::id()));
+        let script = format!("sleep 5 & echo $! > {}; wait", pid_path.display());
+        assert_eq!(run(&script, 200), None);
+        let pid = fs::read_to_string(&pid_path).unwrap();
+        fs::remove_file(&pid_path).unwrap();
+        let stat_path = format!("/proc/{}/stat", pid.trim());
+        let killed = (0..100).any(|_| {
+            thread::sleep(Duration::from_millis(10));
+            fs::read_to_string(&stat_path).map_or(true, |stat| stat.contains(") Z "))
+        });
+        assert!(killed);
+    }
+}
@@ -0,0 +1,194 @@
+use crate::command;
+use crate::config::{CustomCommandConfig, CustomOutputFormat};
+use crate::util;
+use colored::Colorize;
+use serde_json::Value;
+use std::io;
+use std::process::{Command, ExitStatus};
+use std::time::Duration;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+enum CustomError {
+    #[error("Timed out after {0} ms")]
+    Timeout(u64),
+
+    /// The command exited unsuccessfully, keeping its stdout as check scripts often explain the failure there.
+    #[error("Command failed with {status}")]
+    Failed { status: ExitStatus, output: String },
+
+    #[error("Invalid JSON output: {0}")]
+    InvalidJson(#[from] serde_json::Error),
+
+    #[error(transparent)]
+    IOError(#[from] io::Error),
+}
+
+/// Run a command with `sh -c` and return its stdout, killing it and any processes it started if it has not
+/// finished within `timeout_ms`.
+fn run_command(command: &str, timeout_ms: u64) -> Result<String, CustomError> {
+    let output = command::output_with_timeout(
+        Command::new("sh").arg("-c").arg(command),
+        Duration::from_millis(timeout_ms),
+    )?
+    .ok_or(CustomError::Timeout(timeout_ms))?;
+    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+    match output.status.success() {
+        true => Ok(stdout),
+        false => Err(CustomError::Failed {
+            status: output.status,
+            output: stdout,
+        }),
+    }
+}
+
+/// Parse lines of the form `key=value` into rows, ignoring any other lines.
+fn parse_key_value(output: &str) -> Vec<(String, String)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once('=')?;
+            Some((key.trim().to_string(), value.trim().to_string()))
+        })
+        .collect()
+}
+
+/// Flatten a JSON value into rows in the order the keys were output, joining the keys of nested objects with `.` e.g. `replication.lag`.
+fn flatten_json(prefix: &str, value: &Value, rows: &mut Vec<(String, String)>) {
+    match value {
+        Value::Object(object) => {
+            for (key, value) in object {
+                let key = match prefix.is_empty() {
+                    true => key.clone(),
+                    false => format!("{}.{}", prefix, key),
+                };
+                flatten_json(&key, value, rows);
+            }
+        }
+        Value::String(string) => rows.push((prefix.to_string(), string.clone())),
+        value => rows.push((prefix.to_string(), value.to_string())),
+    }
+}
+
+fn parse_json(output: &str) -> Result<Vec<(String, String)>, CustomError> {
+    let mut rows = Vec::new();
+    flatten_json("", &serde_json::from_str(output)?, &mut rows);
+    Ok(rows)
+}
+
+fn print_rows(rows: &[(String, String)], cfg: &CustomCommandConfig, indent: &str) {
+    let header = ["Name", "Value"];
+    let column_widths = util::column_widths(
+        &header,
+        rows.iter()
+            .map(|(key, value)| vec![key.chars().count(), value.chars().count()]),
+    );
+
+    util::print_row(header, &column_widths, Some(indent));
+    for (key, value) in rows {
+        let colour = match (cfg.thresholds.get(key), value.parse::<f64>()) {
+            (Some(cond_colour), Ok(number)) => {
+                Some(util::select_colour_number(number, cond_colour))
+            }
+            _ => None,
+        };
+        util::print_row_colour(
+            util::format_width([key.as_str(), value.as_str()], &column_widths),
+            [None, colour],
+            Some(indent),
+        );
+    }
+}
+
+fn print_command(cfg: &CustomCommandConfig, indent: &str) {
+    println!("{}:", cfg.name);
+    let output = match run_command(&cfg.command, cfg.timeout_ms) {
+        Ok(output) => output,
+        Err(e) => {
+            match &e {
+                CustomError::Failed { output, .. } => {
+                    println!("{}{}", indent, e.to_string().red());
+                    output
+                        .lines()
+                        .for_each(|line| println!("{}{}", indent, line.red()));
+                }
+                _ => println!("{}{}", indent, e),
+            }
+            return;
+        }
+    };
+
+    match cfg.format {
+        CustomOutputFormat::Verbatim => {
+            output
+                .lines()
+                .for_each(|line| println!("{}{}", indent, line));
+        }
+        CustomOutputFormat::KeyValue => print_rows(&parse_key_value(&output), cfg, indent),
+        CustomOutputFormat::Json => match parse_json(&output) {
+            Ok(rows) => print_rows(&rows, cfg, indent),
+            Err(e) => println!("{}{}", indent, e),
+        },
+    }
+}
+
+pub fn print_custom(cfg: &[CustomCommandConfig], indent: &str) {
+    cfg.iter()
+        .for_each(|command| print_command(command, indent));
+}
+
+#[cfg(test)]
+mod test {
+    use crate::components::custom::{self, CustomError};
+
+    fn rows(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_run_command() {
+        assert_eq!(
+            custom::run_command("echo queue=3", 5000).unwrap(),
+            "queue=3\n"
+        );
+        match custom::run_command("echo CRITICAL: 2 replicas down; exit 2", 5000) {
+            Err(CustomError::Failed { status, output }) => {
+                assert_eq!(status.code(), Some(2));
+                assert_eq!(output, "CRITICAL: 2 replicas down\n");
+            }
+            result => panic!("Unexpected result {:?}", result),
+        }
+        assert!(matches!(
+            custom::run_command("sleep 5", 100),
+            Err(CustomError::Timeout(100))
+        ));
+    }
+
+    #[test]
+    fn test_parse_key_value() {
+        assert_eq!(
+            custom::parse_key_value("queue = 3\nignored line\nlag=0.5s\n"),
+            rows(&[("queue", "3"), ("lag", "0.5s")])
+        );
+    }
+
+    #[test]
+    fn test_parse_json() {
+        assert_eq!(
+            custom::parse_json(
+                r#"{"queue": 3, "replication": {"state": "streaming", "lag": 0.5}, "errors": 0}"#
+            )
+            .unwrap(),
+            rows(&[
+                ("queue", "3"),
+                ("replication.state", "streaming"),
+                ("replication.lag", "0.5"),
+                ("errors", "0")
+            ])
+        );
+        assert!(custom::parse_json("not json").is_err());
+    }
+}
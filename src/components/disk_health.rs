@@ -0,0 +1,295 @@
+use crate::command;
+use crate::config::{DiskHealthConfig, UnitsConfig};
+use crate::units;
+use crate::util;
+use colored::Color;
+use serde_json::Value;
+use std::process::Command;
+use std::thread;
+use std::time::Duration;
+
+/// ATA attribute ids of the normalised wear indicators of SSDs, where 100 is new and 0 is worn out.
+const ATA_WEAR_ATTRIBUTES: [u64; 3] = [
+    177, // Wear_Leveling_Count
+    231, // SSD_Life_Left
+    233, // Media_Wearout_Indicator
+];
+
+#[derive(Debug, PartialEq)]
+struct Entry {
+    device: String,
+    model: String,
+    /// Overall health self-assessment, `None` if it could not be read.
+    passed: Option<bool>,
+    reallocated_sectors: Option<u64>,
+    pending_sectors: Option<u64>,
+    power_on_hours: Option<u64>,
+    /// Percentage of the rated endurance of an SSD which has been used.
+    wear_percent: Option<u64>,
+    temperature: Option<u64>,
+    /// Error reported by smartctl when the device could not be read.
+    error: Option<String>,
+}
+
+fn ata_attribute(smart: &Value, id: u64) -> Option<&Value> {
+    smart["ata_smart_attributes"]["table"]
+        .as_array()?
+        .iter()
+        .find(|attribute| attribute["id"].as_u64() == Some(id))
+}
+
+fn ata_raw_value(smart: &Value, id: u64) -> Option<u64> {
+    ata_attribute(smart, id)?["raw"]["value"].as_u64()
+}
+
+fn wear_percent(smart: &Value) -> Option<u64> {
+    let nvme_log = &smart["nvme_smart_health_information_log"];
+    smart["endurance_used"]["current_percent"]
+        .as_u64()
+        .or_else(|| nvme_log["percentage_used"].as_u64())
+        .or_else(|| {
+            ATA_WEAR_ATTRIBUTES.iter().find_map(|id| {
+                let remaining = ata_attribute(smart, *id)?["value"].as_u64()?;
+                Some(100u64.saturating_sub(remaining))
+            })
+        })
+}
+
+fn smartctl_error(smart: &Value) -> Option<String> {
+    smart["smartctl"]["messages"]
+        .as_array()?
+        .iter()
+        .find(|message| message["severity"] == "error")
+        .and_then(|message| message["string"].as_str())
+        .map(|message| message.to_string())
+}
+
+fn error_entry(device: &str, error: String) -> Entry {
+    Entry {
+        device: device.to_string(),
+        model: "N/A".to_string(),
+        passed: None,
+        reallocated_sectors: None,
+        pending_sectors: None,
+        power_on_hours: None,
+        wear_percent: None,
+        temperature: None,
+        error: Some(error),
+    }
+}
+
+/// Parse the output of `smartctl --all --json` for a device.
+fn parse_entry(device: &str, output: &str) -> Entry {
+    let smart: Value = match serde_json::from_str(output) {
+        Ok(smart) => smart,
+        Err(e) => return error_entry(device, format!("Invalid smartctl output: {}", e)),
+    };
+    let nvme_log = &smart["nvme_smart_health_information_log"];
+
+    Entry {
+        device: device.to_string(),
+        model: smart["model_name"].as_str().unwrap_or("N/A").to_string(),
+        passed: smart["smart_status"]["passed"].as_bool(),
+        reallocated_sectors: ata_raw_value(&smart, 5),
+        pending_sectors: ata_raw_value(&smart, 197).or_else(|| nvme_log["media_errors"].as_u64()),
+        power_on_hours: smart["power_on_time"]["hours"].as_u64(),
+        wear_percent: wear_percent(&smart),
+        temperature: smart["temperature"]["current"].as_u64(),
+        error: smartctl_error(&smart),
+    }
+}
+
+fn device_entry(device: &str, timeout_ms: u64) -> Entry {
+    let mut command = Command::new("smartctl");
+    command.arg("--all").arg("--json").arg(device);
+
+    // smartctl uses a non-zero exit status for warnings as well as errors so the output is always parsed.
+    match command::output_with_timeout(&mut command, Duration::from_millis(timeout_ms)) {
+        Ok(Some(raw_output)) => parse_entry(device, &String::from_utf8_lossy(&raw_output.stdout)),
+        Ok(None) => error_entry(
+            device,
+            format!("smartctl timed out after {} ms", timeout_ms),
+        ),
+        Err(e) => error_entry(device, format!("Failed to run smartctl: {}", e)),
+    }
+}
+
+fn format_optional(value: Option<u64>) -> String {
+    value
+        .map(|value| value.to_string())
+        .unwrap_or_else(|| "N/A".to_string())
+}
+
+fn format_health(entry: &Entry) -> String {
+    match (entry.passed, &entry.error) {
+        (Some(true), _) => "PASSED".to_string(),
+        (Some(false), _) => "FAILED".to_string(),
+        (None, Some(error)) => error.clone(),
+        (None, None) => "N/A".to_string(),
+    }
+}
+
+fn health_colour(entry: &Entry) -> Color {
+    match entry.passed {
+        Some(true) => Color::Green,
+        Some(false) => Color::Red,
+        None => Color::Yellow,
+    }
+}
+
+pub fn print_disk_health(cfg: &DiskHealthConfig, units_cfg: &UnitsConfig, indent: &str) {
+    let header = [
+        "Device", "Model", "Health", "Realloc", "Pending", "Power On", "Wear(%)", "Temp",
+    ];
+
+    // Each device is read in its own thread so that drives spinning up are waited for at the same time.
+    let workers = cfg
+        .devices
+        .iter()
+        .map(|device| {
+            let device = device.clone();
+            let timeout_ms = cfg.timeout_ms;
+            thread::spawn(move || device_entry(&device, timeout_ms))
+        })
+        .collect::<Vec<_>>();
+    let entries = cfg
+        .devices
+        .iter()
+        .zip(workers)
+        .map(|(device, worker)| {
+            worker
+                .join()
+                .unwrap_or_else(|_| error_entry(device, "Failed to run smartctl".to_string()))
+        })
+        .collect::<Vec<Entry>>();
+
+    let formatted_entries = entries
+        .iter()
+        .map(|entry| {
+            [
+                entry.device.clone(),
+                entry.model.clone(),
+                format_health(entry),
+                format_optional(entry.reallocated_sectors),
+                format_optional(entry.pending_sectors),
+                entry
+                    .power_on_hours
+                    .map(|hours| format!("{}h", hours))
+                    .unwrap_or_else(|| "N/A".to_string()),
+                format_optional(entry.wear_percent),
+                entry
+                    .temperature
+                    .map(|temperature| {
+                        units::format_temperature(temperature as f32, &units_cfg.temperature)
+                    })
+                    .unwrap_or_else(|| "N/A".to_string()),
+            ]
+        })
+        .collect::<Vec<[String; 8]>>();
+
+    let column_widths = util::column_widths(
+        &header,
+        formatted_entries
+            .iter()
+            .map(|cells| cells.iter().map(|cell| cell.chars().count()).collect()),
+    );
+
+    println!("Disk health:");
+    util::print_row(header, &column_widths, Some(indent));
+    entries
+        .iter()
+        .zip(formatted_entries.iter())
+        .for_each(|(entry, cells)| {
+            let formatted_cells =
+                util::format_width(cells.iter().map(|cell| cell.as_str()), &column_widths);
+            let colours = [
+                None,
+                None,
+                Some(health_colour(entry)),
+                entry
+                    .reallocated_sectors
+                    .map(|sectors| util::select_colour_number(sectors, &cfg.sectors)),
+                entry
+                    .pending_sectors
+                    .map(|sectors| util::select_colour_number(sectors, &cfg.sectors)),
+                match (entry.power_on_hours, &cfg.power_on_hours) {
+                    (Some(hours), Some(cond_colour)) => {
+                        Some(util::select_colour_number(hours, cond_colour))
+                    }
+                    _ => None,
+                },
+                entry
+                    .wear_percent
+                    .map(|wear| util::select_colour_number(wear, &cfg.wear)),
+                entry.temperature.map(|temperature| {
+                    let temperature =
+                        units::convert_temperature(temperature as f32, &units_cfg.temperature);
+                    util::select_colour_number(temperature.round() as u64, &cfg.temperature)
+                }),
+            ];
+            util::print_row_colour(formatted_cells, colours, Some(indent))
+        });
+}
+
+#[cfg(test)]
+mod test {
+    use crate::components::disk_health::{self, Entry};
+
+    #[test]
+    fn test_parse_hdd() {
+        let output = include_str!("../../resources/test/smartctl_hdd.json");
+        assert_eq!(
+            disk_health::parse_entry("/dev/sda", output),
+            Entry {
+                device: "/dev/sda".to_string(),
+                model: "WDC WD40EFRX-68N32N0".to_string(),
+                passed: Some(true),
+                reallocated_sectors: Some(8),
+                pending_sectors: Some(2),
+                power_on_hours: Some(28712),
+                wear_percent: None,
+                temperature: Some(37),
+                error: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_sata_ssd() {
+        let output = include_str!("../../resources/test/smartctl_sata_ssd.json");
+        let entry = disk_health::parse_entry("/dev/sdb", output);
+        assert_eq!(entry.passed, Some(true));
+        assert_eq!(entry.reallocated_sectors, Some(0));
+        assert_eq!(entry.pending_sectors, None);
+        assert_eq!(entry.wear_percent, Some(7));
+        assert_eq!(entry.temperature, Some(32));
+    }
+
+    #[test]
+    fn test_parse_nvme() {
+        let output = include_str!("../../resources/test/smartctl_nvme.json");
+        let entry = disk_health::parse_entry("/dev/nvme0", output);
+        assert_eq!(entry.passed, Some(false));
+        assert_eq!(disk_health::format_health(&entry), "FAILED");
+        assert_eq!(entry.reallocated_sectors, None);
+        assert_eq!(entry.pending_sectors, Some(0));
+        assert_eq!(entry.power_on_hours, Some(9120));
+        assert_eq!(entry.wear_percent, Some(12));
+        assert_eq!(entry.temperature, Some(41));
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let output = include_str!("../../resources/test/smartctl_permission_denied.json");
+        let entry = disk_health::parse_entry("/dev/sda", output);
+        assert_eq!(entry.passed, None);
+        assert_eq!(
+            disk_health::format_health(&entry),
+            "Smartctl open device: /dev/sda failed: Permission denied"
+        );
+
+        let entry = disk_health::parse_entry("/dev/sda", "");
+        assert_eq!(entry.passed, None);
+        assert!(entry.error.is_some());
+    }
+}
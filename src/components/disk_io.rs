@@ -0,0 +1,219 @@
+use crate::config::{DiskIoConfig, UnitsConfig};
+use crate::units;
+use crate::util;
+use bytesize::ByteSize;
+use std::fs;
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Size of a sector as used by `/proc/diskstats`, independent of the sector size of the device.
+const SECTOR_SIZE: u64 = 512;
+
+/// Cumulative counters for a single block device from `/proc/diskstats`.
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+struct Sample {
+    reads: u64,
+    sectors_read: u64,
+    ms_reading: u64,
+    writes: u64,
+    sectors_written: u64,
+    ms_writing: u64,
+    ms_doing_io: u64,
+}
+
+/// Rates calculated from the difference between two samples.
+#[derive(Debug, PartialEq)]
+struct Entry {
+    device: String,
+    read_per_sec: ByteSize,
+    write_per_sec: ByteSize,
+    iops: f64,
+    latency_ms: f64,
+    utilisation: f32,
+}
+
+fn parse_diskstats(contents: &str) -> Vec<(String, Sample)> {
+    contents
+        .lines()
+        .filter_map(|line| {
+            let items = line.split_whitespace().collect::<Vec<&str>>();
+            if items.len() < 14 {
+                return None;
+            }
+            let field = |index: usize| items[index].parse::<u64>().ok();
+            Some((
+                items[2].to_string(),
+                Sample {
+                    reads: field(3)?,
+                    sectors_read: field(5)?,
+                    ms_reading: field(6)?,
+                    writes: field(7)?,
+                    sectors_written: field(9)?,
+                    ms_writing: field(10)?,
+                    ms_doing_io: field(12)?,
+                },
+            ))
+        })
+        .collect()
+}
+
+fn read_diskstats() -> Vec<(String, Sample)> {
+    parse_diskstats(&fs::read_to_string("/proc/diskstats").unwrap_or_default())
+}
+
+fn entry(device: &str, before: &Sample, after: &Sample, elapsed: Duration) -> Entry {
+    let seconds = elapsed.as_secs_f64().max(f64::EPSILON);
+    let reads = after.reads.saturating_sub(before.reads);
+    let writes = after.writes.saturating_sub(before.writes);
+    let ios = reads + writes;
+    let ms_waiting = after.ms_reading.saturating_sub(before.ms_reading)
+        + after.ms_writing.saturating_sub(before.ms_writing);
+    let ms_doing_io = after.ms_doing_io.saturating_sub(before.ms_doing_io);
+
+    let per_sec =
+        |sectors: u64| ByteSize::b((sectors as f64 * SECTOR_SIZE as f64 / seconds) as u64);
+
+    Entry {
+        device: device.to_string(),
+        read_per_sec: per_sec(after.sectors_read.saturating_sub(before.sectors_read)),
+        write_per_sec: per_sec(after.sectors_written.saturating_sub(before.sectors_written)),
+        iops: ios as f64 / seconds,
+        latency_ms: match ios {
+            0 => 0.0,
+            _ => ms_waiting as f64 / ios as f64,
+        },
+        utilisation: ((ms_doing_io as f64 / (seconds * 1000.0)) * 100.0).min(100.0) as f32,
+    }
+}
+
+/// Whole block devices appear in `/sys/block` while partitions do not.
+fn is_whole_device(device: &str) -> bool {
+    Path::new("/sys/block").join(device).exists()
+}
+
+pub fn print_disk_io(cfg: &DiskIoConfig, units_cfg: &UnitsConfig, indent: &str) {
+    let header = ["Device", "Read/s", "Write/s", "IOPS", "Latency", "Util(%)"];
+
+    let included = |device: &str| {
+        is_whole_device(device)
+            && !cfg
+                .exclude_prefixes
+                .iter()
+                .any(|prefix| device.starts_with(prefix))
+    };
+
+    let start = Instant::now();
+    let before = read_diskstats();
+    thread::sleep(Duration::from_millis(cfg.sample_interval_ms));
+    let after = read_diskstats();
+    let elapsed = start.elapsed();
+
+    let entries = after
+        .iter()
+        .filter(|(device, _)| included(device))
+        .filter_map(|(device, after_sample)| {
+            let (_, before_sample) = before.iter().find(|(name, _)| name == device)?;
+            Some(entry(device, before_sample, after_sample, elapsed))
+        })
+        .collect::<Vec<Entry>>();
+
+    let formatted_entries = entries
+        .iter()
+        .map(|entry| {
+            [
+                entry.device.clone(),
+                units::format_bytes(entry.read_per_sec.as_u64(), &units_cfg.bytes),
+                units::format_bytes(entry.write_per_sec.as_u64(), &units_cfg.bytes),
+                format!("{:.1}", entry.iops),
+                format!("{:.1} ms", entry.latency_ms),
+                format!("{:.1}", entry.utilisation),
+            ]
+        })
+        .collect::<Vec<[String; 6]>>();
+
+    let column_widths = util::column_widths(
+        &header,
+        formatted_entries
+            .iter()
+            .map(|cells| cells.iter().map(|cell| cell.len()).collect()),
+    );
+
+    println!("Disk I/O:");
+    util::print_row(header, &column_widths, Some(indent));
+    entries
+        .iter()
+        .zip(formatted_entries.iter())
+        .for_each(|(entry, cells)| {
+            let formatted_cells =
+                util::format_width(cells.iter().map(|cell| cell.as_str()), &column_widths);
+            let colours = [
+                None,
+                None,
+                None,
+                None,
+                None,
+                Some(util::select_colour_number(
+                    entry.utilisation,
+                    &cfg.utilisation,
+                )),
+            ];
+            util::print_row_colour(formatted_cells, colours, Some(indent))
+        });
+}
+
+#[cfg(test)]
+mod test {
+    use crate::components::disk_io::{self, Sample};
+    use bytesize::ByteSize;
+    use std::time::Duration;
+
+    #[test]
+    fn test_parse_diskstats() {
+        let contents = "   7       0 loop0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0
+ 253       0 vda 9786 3005 1021966 4131 54207 43370 2386168 66453 0 52432 76187 0 0 0 0 5345 5602
+ 253       1 vda1";
+        let samples = disk_io::parse_diskstats(contents);
+        assert_eq!(samples.len(), 2);
+        assert_eq!(samples[0].0, "loop0");
+        assert_eq!(
+            samples[1],
+            (
+                "vda".to_string(),
+                Sample {
+                    reads: 9786,
+                    sectors_read: 1021966,
+                    ms_reading: 4131,
+                    writes: 54207,
+                    sectors_written: 2386168,
+                    ms_writing: 66453,
+                    ms_doing_io: 52432,
+                }
+            )
+        );
+    }
+
+    #[test]
+    fn test_entry() {
+        let before = Sample::default();
+        let after = Sample {
+            reads: 100,
+            sectors_read: 2048,
+            ms_reading: 300,
+            writes: 100,
+            sectors_written: 4096,
+            ms_writing: 100,
+            ms_doing_io: 500,
+        };
+        let entry = disk_io::entry("vda", &before, &after, Duration::from_secs(2));
+        assert_eq!(entry.read_per_sec, ByteSize::kib(512));
+        assert_eq!(entry.write_per_sec, ByteSize::mib(1));
+        assert_eq!(entry.iops, 100.0);
+        assert_eq!(entry.latency_ms, 2.0);
+        assert_eq!(entry.utilisation, 25.0);
+
+        let idle = disk_io::entry("vda", &after, &after, Duration::from_secs(2));
+        assert_eq!(idle.latency_ms, 0.0);
+        assert_eq!(idle.utilisation, 0.0);
+    }
+}
@@ -1,114 +1,990 @@
-use crate::config::{ConditionalColour, StorageConfig};
+use crate::components::storage_history::{self, Sample};
+use crate::config::{
+    ConditionalColour, MountConfig, MountFilterConfig, StorageConfig, StorageHistoryConfig,
+    StorageSort, UnitsConfig,
+};
+use crate::units;
 use crate::util;
 use bytesize::ByteSize;
+use chrono::Utc;
 use colored::*;
-use std::str;
-use sysinfo::{Disk, DiskExt, DiskType};
-use sysinfo::{System, SystemExt};
+use glob::MatchOptions;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::ffi::CString;
+use std::fs;
+use std::mem;
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
 
 #[derive(Debug)]
 struct Entry<'a> {
-    mount_point: &'a str,
+    /// Mount points of the filesystem. The first is the primary mount point and any others are duplicates
+    /// such as bind mounts or other subvolumes of the same filesystem.
+    mount_points: Vec<&'a str>,
+    /// Label from the configuration shown instead of the mount point.
+    label: Option<&'a str>,
+    device: &'a str,
     disk_type: &'a str,
     fs_type: &'a str,
     used_percent: u64,
     used: ByteSize,
     total: ByteSize,
+    inodes: Option<InodeUsage>,
+    /// Estimated number of days until the filesystem is full based on the recorded usage history.
+    days_until_full: Option<f64>,
+    /// The filesystem did not respond in time so its usage is unknown.
+    stale: bool,
 }
 
-fn entry(disk: &Disk) -> Entry {
-    let used = ByteSize::b(disk.total_space() - disk.available_space());
-    let total = ByteSize::b(disk.total_space());
-    let used_percent = (100 * used.as_u64()) / total.as_u64();
+impl Entry<'_> {
+    fn used_ratio(&self) -> f32 {
+        match self.total.as_u64() {
+            0 => 0.0,
+            total => self.used.as_u64() as f32 / total as f32,
+        }
+    }
+
+    fn free(&self) -> ByteSize {
+        ByteSize::b(self.total.as_u64() - self.used.as_u64())
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct InodeUsage {
+    used: u64,
+    total: u64,
+}
+
+impl InodeUsage {
+    fn used_ratio(&self) -> f32 {
+        self.used as f32 / self.total as f32
+    }
+
+    fn used_percent(&self) -> u64 {
+        (100 * self.used) / self.total
+    }
+}
+
+/// A filesystem mounted on the system, read from `/proc/mounts`.
+#[derive(Debug, PartialEq)]
+struct Mount {
+    device: String,
+    mount_point: String,
+    fs_type: String,
+}
+
+/// Parse `/proc/mounts`.
+fn parse_mounts(contents: &str) -> Vec<Mount> {
+    contents
+        .lines()
+        .filter_map(|line| {
+            let mut items = line.split_whitespace();
+            Some(Mount {
+                device: items.next()?.to_string(),
+                mount_point: unescape_mount_point(items.next()?),
+                fs_type: items.next()?.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Space and inode usage of a filesystem.
+#[derive(Debug)]
+struct FsStats {
+    total: u64,
+    available: u64,
+    inodes: Option<InodeUsage>,
+}
+
+/// Get the usage of the filesystem mounted at `mount_point` using `statvfs`.
+///
+/// The inode usage is `None` if the filesystem does not have a fixed number of inodes e.g. btrfs.
+fn fs_stats(mount_point: &str) -> Option<FsStats> {
+    let path = CString::new(Path::new(mount_point).as_os_str().as_bytes()).ok()?;
+    // SAFETY: `statvfs` is a plain C struct for which all zero bytes is a valid value.
+    let mut stat: libc::statvfs = unsafe { mem::zeroed() };
+    // SAFETY: `path` is a valid NUL terminated string and `stat` is a valid pointer for the duration of the call.
+    if unsafe { libc::statvfs(path.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
 
-    let file_system = match str::from_utf8(disk.file_system()) {
-        Ok(v) => v,
-        Err(e) => panic!("Invalid UTF-8 sequence: {}", e),
+    let block_size = stat.f_frsize as u64;
+    let total_inodes = stat.f_files as u64;
+    let free_inodes = stat.f_ffree as u64;
+    Some(FsStats {
+        total: block_size.saturating_mul(stat.f_blocks as u64),
+        available: block_size.saturating_mul(stat.f_bavail as u64),
+        inodes: match total_inodes {
+            0 => None,
+            _ => Some(InodeUsage {
+                used: total_inodes.saturating_sub(free_inodes),
+                total: total_inodes,
+            }),
+        },
+    })
+}
+
+#[derive(Debug)]
+enum MountStats {
+    Ok(FsStats),
+    Failed,
+    /// The filesystem did not respond before the timeout e.g. an unreachable network share.
+    Stale,
+}
+
+/// Stat each mount in its own worker thread so that a hung network or FUSE filesystem cannot block the report.
+///
+/// Mounts which have not responded within `timeout` of the workers being started are stale.
+/// Their workers are left blocked in the background and end with the process.
+fn stat_mounts(mounts: &[Mount], timeout: Duration) -> Vec<MountStats> {
+    let deadline = Instant::now() + timeout;
+    let receivers = mounts
+        .iter()
+        .map(|mount| {
+            let (sender, receiver) = mpsc::channel();
+            let mount_point = mount.mount_point.clone();
+            thread::spawn(move || sender.send(fs_stats(&mount_point)));
+            receiver
+        })
+        .collect::<Vec<_>>();
+
+    receivers
+        .into_iter()
+        .map(|receiver| {
+            match receiver.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                Ok(Some(stats)) => MountStats::Ok(stats),
+                Ok(None) => MountStats::Failed,
+                Err(mpsc::RecvTimeoutError::Timeout) => MountStats::Stale,
+                Err(mpsc::RecvTimeoutError::Disconnected) => MountStats::Failed,
+            }
+        })
+        .collect()
+}
+
+const MOUNT_GLOB_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+fn matches_filter(
+    mount_point: &str,
+    device: &str,
+    fs_type: &str,
+    filter: &MountFilterConfig,
+) -> bool {
+    filter
+        .fs_types
+        .iter()
+        .any(|filter_fs_type| filter_fs_type == fs_type)
+        || filter.devices.iter().any(|pattern| pattern.matches(device))
+        || filter
+            .mount_globs
+            .iter()
+            .any(|pattern| pattern.matches_with(mount_point, MOUNT_GLOB_OPTIONS))
+        || filter
+            .mount_regexes
+            .iter()
+            .any(|regex| regex.is_match(mount_point))
+}
+
+/// Pseudo filesystems which never hold any data worth showing.
+const PSEUDO_FS_TYPES: [&str; 11] = [
+    "rootfs",
+    "sysfs",
+    "proc",
+    "tmpfs",
+    "devtmpfs",
+    "cgroup",
+    "cgroup2",
+    "pstore",
+    "squashfs",
+    "rpc_pipefs",
+    "iso9660",
+];
+
+/// Whether a mount is a pseudo filesystem or is under `/sys`, `/proc` or `/run` other than `/run/media`.
+fn is_system_mount(mount_point: &str, device: &str, fs_type: &str) -> bool {
+    let is_under = |dir: &str| {
+        mount_point
+            .strip_prefix(dir)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
     };
+    PSEUDO_FS_TYPES.contains(&fs_type)
+        || device.starts_with("sunrpc")
+        || is_under("/sys")
+        || is_under("/proc")
+        || (is_under("/run") && !is_under("/run/media"))
+}
+
+fn is_included(mount_point: &str, device: &str, fs_type: &str, cfg: &StorageConfig) -> bool {
+    !cfg.exclude_prefixes
+        .iter()
+        .any(|prefix| mount_point.starts_with(prefix))
+        && cfg
+            .include
+            .as_ref()
+            .is_none_or(|include| matches_filter(mount_point, device, fs_type, include))
+        && !matches_filter(mount_point, device, fs_type, &cfg.exclude)
+        && (!cfg.default_exclude || !is_system_mount(mount_point, device, fs_type))
+        && (!cfg.only_labelled || cfg.labels.contains_key(mount_point))
+}
 
-    let disk_type = match disk.type_() {
-        DiskType::HDD => "HDD",
-        DiskType::SSD => "SSD",
-        DiskType::Unknown(_) => "N/A",
+/// Read whether the disk behind a device is rotational from `/sys/block/<disk>/queue/rotational`.
+fn disk_type(device: &str) -> &'static str {
+    let rotational = physical_disk(device).and_then(|disk| {
+        fs::read_to_string(Path::new("/sys/block").join(disk).join("queue/rotational")).ok()
+    });
+    match rotational.as_deref().map(str::trim) {
+        Some("1") => "HDD",
+        Some("0") => "SSD",
+        _ => "N/A",
+    }
+}
+
+/// Create the entry for a mount. Returns `None` if the mount could not be read or has no space e.g. a pseudo filesystem.
+fn entry<'a>(mount: &'a Mount, stats: MountStats, show_inodes: bool) -> Option<Entry<'a>> {
+    let mut entry = Entry {
+        mount_points: vec![mount.mount_point.as_str()],
+        label: None,
+        device: &mount.device,
+        disk_type: disk_type(&mount.device),
+        fs_type: &mount.fs_type,
+        used_percent: 0,
+        used: ByteSize::b(0),
+        total: ByteSize::b(0),
+        inodes: None,
+        days_until_full: None,
+        stale: false,
     };
+    match stats {
+        MountStats::Ok(stats) if stats.total > 0 => {
+            entry.used = ByteSize::b(stats.total.saturating_sub(stats.available));
+            entry.total = ByteSize::b(stats.total);
+            entry.used_percent = (100 * entry.used.as_u64()) / stats.total;
+            if show_inodes {
+                entry.inodes = stats.inodes;
+            }
+        }
+        MountStats::Ok(_) | MountStats::Failed => return None,
+        MountStats::Stale => entry.stale = true,
+    }
+    Some(entry)
+}
 
-    Entry {
-        mount_point: disk
-            .mount_point()
+/// Unescape the octal escapes used for whitespace and backslashes in `/proc/self/mountinfo` e.g. `\\040` for a space.
+fn unescape_mount_point(mount_point: &str) -> String {
+    let mut unescaped = String::with_capacity(mount_point.len());
+    let mut rest = mount_point;
+    while let Some(index) = rest.find('\\') {
+        unescaped.push_str(&rest[..index]);
+        let escape = rest.get(index + 1..index + 4).unwrap_or("");
+        match u8::from_str_radix(escape, 8) {
+            Ok(byte) if escape.len() == 3 => {
+                unescaped.push(byte as char);
+                rest = &rest[index + 4..];
+            }
+            _ => {
+                unescaped.push('\\');
+                rest = &rest[index + 1..];
+            }
+        }
+    }
+    unescaped.push_str(rest);
+    unescaped
+}
+
+/// Parse `/proc/self/mountinfo` into a map from mount point to the `major:minor` id of the mounted filesystem.
+///
+/// Bind mounts and btrfs subvolumes share the id of the filesystem they belong to.
+fn parse_mountinfo(contents: &str) -> HashMap<String, String> {
+    contents
+        .lines()
+        .filter_map(|line| {
+            let mut items = line.split_whitespace();
+            let device_id = items.nth(2)?;
+            let mount_point = items.nth(1)?;
+            Some((unescape_mount_point(mount_point), device_id.to_string()))
+        })
+        .collect()
+}
+
+/// Key identifying the filesystem behind an entry so that duplicate mounts can be collapsed.
+fn filesystem_key(entry: &Entry, mountinfo: &HashMap<String, String>) -> String {
+    match (entry.fs_type, mountinfo.get(entry.mount_points[0])) {
+        // The usage of a stale mount is unknown so it can only be matched by its mount point.
+        _ if entry.stale => entry.mount_points[0].to_string(),
+        // Each overlay mount is a separate filesystem but reports the usage of the filesystem holding its upper directory.
+        ("overlay", _) => format!("overlay {} {}", entry.used.as_u64(), entry.total.as_u64()),
+        (_, Some(device_id)) => device_id.clone(),
+        (_, None) => entry.mount_points[0].to_string(),
+    }
+}
+
+/// Collapse entries of the same filesystem into a single entry listing all of their mount points.
+fn deduplicate<'a>(entries: Vec<Entry<'a>>, mountinfo: &HashMap<String, String>) -> Vec<Entry<'a>> {
+    let mut deduplicated: Vec<(String, Entry)> = Vec::new();
+    for entry in entries {
+        let key = filesystem_key(&entry, mountinfo);
+        match deduplicated
+            .iter_mut()
+            .find(|(existing_key, _)| *existing_key == key)
+        {
+            Some((_, existing)) => existing.mount_points.extend(entry.mount_points),
+            None => deduplicated.push((key, entry)),
+        }
+    }
+
+    deduplicated
+        .into_iter()
+        .map(|(_, mut entry)| {
+            // The shortest mount point is most likely the root of the filesystem.
+            entry
+                .mount_points
+                .sort_by_key(|mount_point| mount_point.len());
+            entry
+        })
+        .collect()
+}
+
+/// Resolve a block device name to the physical disk it is on, following partitions to their disk
+/// and device mapper devices (e.g. LVM or LUKS) to their first underlying device.
+fn block_device_disk(name: &str) -> Option<String> {
+    let sys_path = Path::new("/sys/class/block").join(name);
+    if !sys_path.exists() {
+        return None;
+    }
+    if sys_path.join("partition").exists() {
+        let disk_path = fs::canonicalize(&sys_path).ok()?;
+        return disk_path
+            .parent()?
+            .file_name()?
             .to_str()
-            .expect("Failed to get disk name"),
-        disk_type,
-        fs_type: file_system,
-        used_percent,
-        used,
-        total,
+            .map(|disk| disk.to_string());
     }
+    if let Some(Ok(slave)) = fs::read_dir(sys_path.join("slaves"))
+        .ok()
+        .and_then(|mut slaves| slaves.next())
+    {
+        return block_device_disk(slave.file_name().to_str()?);
+    }
+    Some(name.to_string())
 }
 
-fn print_entry_bar(
-    entry: &Entry,
+/// Find the physical disk of a device e.g. `/dev/sda1` gives `sda`.
+/// Returns `None` for filesystems which are not backed by a block device e.g. tmpfs.
+fn physical_disk(device: &str) -> Option<String> {
+    let device_path = fs::canonicalize(device).unwrap_or_else(|_| Path::new(device).to_path_buf());
+    block_device_disk(device_path.file_name()?.to_str()?)
+}
+
+/// Group the indices of entries by the physical disk they are on, keeping the order in which disks first appear.
+/// Entries not backed by a block device are placed last in a group named `Other`.
+fn group_by_disk(entries: &[Entry]) -> Vec<(String, Vec<usize>)> {
+    let mut groups: Vec<(String, Vec<usize>)> = Vec::new();
+    let mut other = Vec::new();
+    for (index, entry) in entries.iter().enumerate() {
+        match physical_disk(entry.device) {
+            Some(disk) => match groups.iter_mut().find(|(name, _)| *name == disk) {
+                Some((_, group)) => group.push(index),
+                None => groups.push((disk, vec![index])),
+            },
+            None => other.push(index),
+        }
+    }
+    if !other.is_empty() {
+        groups.push(("Other".to_string(), other));
+    }
+    groups
+}
+
+fn print_usage_bar(
+    used_ratio: f32,
     bar_width: usize,
-    cfg: &ConditionalColour<f32>,
+    fill: &str,
+    colour: Color,
     indent: Option<&str>,
 ) {
-    let used_ratio = entry.used.as_u64() as f32 / entry.total.as_u64() as f32;
     let used_bar_width = (used_ratio * bar_width as f32) as usize;
     println!(
         "{}[{}{}]",
         indent.unwrap_or(""),
-        "=".repeat(used_bar_width)
-            .color(util::select_colour_number(used_ratio, cfg)),
-        "=".repeat(bar_width - used_bar_width)
+        fill.repeat(used_bar_width).color(colour),
+        fill.repeat(bar_width - used_bar_width)
     );
 }
 
-pub fn print_disks(sys: &mut System, cfg: &StorageConfig, indent: &str) {
-    let header = ["Mount", "Type", "Filesystem", "Used(%)", "Used", "Total"];
+/// Find the configuration for an entry from any of its mount points.
+fn mount_config<'c>(entry: &Entry, cfg: &'c StorageConfig) -> Option<&'c MountConfig> {
+    entry
+        .mount_points
+        .iter()
+        .find_map(|mount_point| cfg.mounts.get(*mount_point))
+}
+
+/// Select the colour of the usage bar of an entry.
+/// Free space thresholds of the mount take precedence, followed by the usage colouring of the mount
+/// and then the usage colouring of the whole section.
+fn usage_colour(entry: &Entry, cfg: &StorageConfig) -> Color {
+    let mount_cfg = mount_config(entry, cfg);
+    if let Some(free_colouring) = mount_cfg.and_then(|mount_cfg| mount_cfg.free_colouring.as_ref())
+    {
+        return util::select_colour_below(entry.free().as_u64(), free_colouring);
+    }
+    let usage_colouring = mount_cfg
+        .and_then(|mount_cfg| mount_cfg.usage_colouring.as_ref())
+        .unwrap_or(&cfg.usage_colouring);
+    util::select_colour_number(entry.used_ratio(), usage_colouring)
+}
+
+fn print_entry_bar(entry: &Entry, bar_width: usize, cfg: &StorageConfig, indent: Option<&str>) {
+    print_usage_bar(
+        entry.used_ratio(),
+        bar_width,
+        "=",
+        usage_colour(entry, cfg),
+        indent,
+    );
+}
+
+fn print_inode_bar(
+    entry: &Entry,
+    bar_width: usize,
+    cfg: &ConditionalColour<f32>,
+    indent: Option<&str>,
+) {
+    if let Some(inodes) = &entry.inodes {
+        let colour = util::select_colour_number(inodes.used_ratio(), cfg);
+        print_usage_bar(inodes.used_ratio(), bar_width, "-", colour, indent);
+    }
+}
+
+fn sort_entries(entries: &mut [Entry], sort_by: &StorageSort) {
+    match sort_by {
+        StorageSort::Mount => entries.sort_by(|a, b| a.mount_points[0].cmp(b.mount_points[0])),
+        StorageSort::UsedPercent => entries.sort_by(|a, b| {
+            b.used_ratio()
+                .partial_cmp(&a.used_ratio())
+                .unwrap_or(Ordering::Equal)
+        }),
+        StorageSort::Free => entries.sort_by_key(|entry| entry.free()),
+    }
+}
+
+/// Record the current usage of each entry in the history file and estimate when each entry will be full.
+fn update_history(entries: &mut [Entry], cfg: &StorageHistoryConfig) {
+    let path = match storage_history::history_path(cfg) {
+        Ok(path) => path,
+        Err(e) => {
+            eprintln!("Could not find the storage history file: {}", e);
+            return;
+        }
+    };
+
+    let mut history = storage_history::load(&path);
+    let now = Utc::now().timestamp();
+    history.prune(now - cfg.max_age_days * 24 * 60 * 60);
+    for entry in entries.iter_mut().filter(|entry| !entry.stale) {
+        let mount_point = entry.mount_points[0];
+        let sample = Sample {
+            timestamp: now,
+            used: entry.used.as_u64(),
+        };
+        history.record(mount_point, sample, cfg.min_sample_interval_minutes * 60);
+        entry.days_until_full = history
+            .mounts
+            .get(mount_point)
+            .and_then(|samples| storage_history::days_until_full(samples, entry.free().as_u64()));
+    }
+
+    if let Err(e) = storage_history::save(&path, &history) {
+        eprintln!(
+            "Failed to save storage history to \"{}\": {}",
+            path.to_string_lossy(),
+            e
+        );
+    }
+}
+
+fn format_days_until_full(days_until_full: Option<f64>) -> String {
+    match days_until_full {
+        Some(days) if days < 1.0 => "<1 day".to_string(),
+        Some(days) if days < 1.5 => "~1 day".to_string(),
+        Some(days) => format!("~{:.0} days", days),
+        None => "-".to_string(),
+    }
+}
 
-    sys.refresh_disks_list();
+fn format_entry(
+    entry: &Entry,
+    show_inodes: bool,
+    show_full_in: bool,
+    units_cfg: &UnitsConfig,
+) -> Vec<String> {
+    let mut cells = vec![
+        entry.label.unwrap_or(entry.mount_points[0]).to_string(),
+        entry.disk_type.to_string(),
+        entry.fs_type.to_string(),
+    ];
+    match entry.stale {
+        true => cells.extend(["stale".to_string(), "N/A".to_string(), "N/A".to_string()]),
+        false => cells.extend([
+            entry.used_percent.to_string(),
+            units::format_bytes(entry.used.as_u64(), &units_cfg.bytes),
+            units::format_bytes(entry.total.as_u64(), &units_cfg.bytes),
+        ]),
+    }
+    if show_full_in {
+        cells.push(format_days_until_full(entry.days_until_full));
+    }
+    if show_inodes {
+        match &entry.inodes {
+            Some(inodes) => cells.extend([
+                inodes.used_percent().to_string(),
+                inodes.used.to_string(),
+                inodes.total.to_string(),
+            ]),
+            None => cells.extend(["N/A".to_string(), "N/A".to_string(), "N/A".to_string()]),
+        }
+    }
+    cells
+}
 
-    let entries = sys
-        .disks()
+fn print_entry(
+    entry: &Entry,
+    cells: &[String],
+    column_widths: &[usize],
+    bar_width: usize,
+    cfg: &StorageConfig,
+    indent: &str,
+) {
+    let formatted_cells = util::format_width(cells.iter().map(|cell| cell.as_str()), column_widths);
+    let inode_colour = match (&entry.inodes, &cfg.inode_colouring) {
+        (Some(inodes), Some(inode_colouring)) => Some(util::select_colour_number(
+            inodes.used_ratio(),
+            inode_colouring,
+        )),
+        _ => None,
+    };
+    let mut colours = vec![None; 6];
+    if entry.stale {
+        colours[3] = Some(Color::Red);
+    }
+    if let Some(history_cfg) = &cfg.history {
+        colours.push(
+            entry
+                .days_until_full
+                .map(|days| util::select_colour_below(days, &history_cfg.days_remaining_colouring)),
+        );
+    }
+    if cfg.inode_colouring.is_some() {
+        colours.extend([inode_colour, None, None]);
+    }
+    util::print_row_colour(formatted_cells, colours, Some(indent));
+    for mount_point in entry.mount_points.iter().skip(1) {
+        util::print_row(
+            [format!("  {}", mount_point).as_str()],
+            column_widths,
+            Some(indent),
+        );
+    }
+    if entry.stale {
+        return;
+    }
+    print_entry_bar(entry, bar_width, cfg, Some(indent));
+    if let (true, Some(inode_colouring)) = (cfg.inode_bar, &cfg.inode_colouring) {
+        print_inode_bar(entry, bar_width, inode_colouring, Some(indent));
+    }
+}
+
+pub fn print_disks(cfg: &StorageConfig, units_cfg: &UnitsConfig, indent: &str) {
+    let mut header = vec!["Mount", "Type", "Filesystem", "Used(%)", "Used", "Total"];
+    let show_inodes = cfg.inode_colouring.is_some();
+    if cfg.history.is_some() {
+        header.push("Full In");
+    }
+    if show_inodes {
+        header.extend(["Inodes(%)", "Inodes Used", "Inodes Total"]);
+    }
+
+    // Filtering happens before the mounts are statted so that excluded mounts can never block the report.
+    let mounts = parse_mounts(&fs::read_to_string("/proc/mounts").unwrap_or_default())
+        .into_iter()
+        .filter(|mount| is_included(&mount.mount_point, &mount.device, &mount.fs_type, cfg))
+        .collect::<Vec<Mount>>();
+    let stats = stat_mounts(&mounts, Duration::from_millis(cfg.stat_timeout_ms));
+    let mut entries = mounts
         .iter()
-        .filter(|disk| {
-            let path = disk.mount_point().to_string_lossy();
-            !cfg.exclude_prefixes
-                .iter()
-                .any(|prefix| path.starts_with(prefix))
-        })
-        .map(|disk| entry(&disk))
+        .zip(stats)
+        .filter_map(|(mount, stats)| entry(mount, stats, show_inodes))
         .collect::<Vec<Entry>>();
 
-    let column_widths = util::column_widths(
+    if cfg.deduplicate {
+        let mountinfo =
+            parse_mountinfo(&fs::read_to_string("/proc/self/mountinfo").unwrap_or_default());
+        entries = deduplicate(entries, &mountinfo);
+    }
+
+    for entry in entries.iter_mut() {
+        entry.label = entry
+            .mount_points
+            .iter()
+            .find_map(|mount_point| cfg.labels.get(*mount_point))
+            .map(|label| label.as_str());
+    }
+
+    if let Some(history_cfg) = &cfg.history {
+        update_history(&mut entries, history_cfg);
+    }
+
+    if let Some(sort_by) = &cfg.sort_by {
+        sort_entries(&mut entries, sort_by);
+    }
+
+    let formatted_entries = entries
+        .iter()
+        .map(|entry| format_entry(entry, show_inodes, cfg.history.is_some(), units_cfg))
+        .collect::<Vec<Vec<String>>>();
+
+    let mut column_widths = util::column_widths(
         &header,
-        entries.iter().map(|entry| {
-            vec![
-                entry.mount_point.len(),
-                entry.disk_type.len(),
-                entry.fs_type.len(),
-                entry.used_percent.to_string().len(),
-                entry.used.to_string().len(),
-                entry.total.to_string().len(),
-            ]
-        }),
+        formatted_entries
+            .iter()
+            .map(|cells| cells.iter().map(|cell| cell.len()).collect()),
     );
+    column_widths[0] = entries
+        .iter()
+        .flat_map(|entry| entry.mount_points.iter().skip(1))
+        .map(|mount_point| mount_point.len() + 2)
+        .fold(column_widths[0], usize::max);
     let bar_width = column_widths.iter().sum::<usize>() + column_widths.len() * 2;
 
     println!("Storage:");
-    util::print_row(header, &column_widths, Some(indent));
-    entries.iter().for_each(|entry| {
-        util::print_row(
-            [
-                entry.mount_point,
-                entry.disk_type,
-                entry.fs_type,
-                &entry.used_percent.to_string(),
-                &entry.used.to_string(),
-                &entry.total.to_string(),
-            ],
-            &column_widths,
-            Some(indent),
+    match cfg.group_by_disk {
+        true => {
+            let nested_indent = indent.repeat(2);
+            util::print_row(header, &column_widths, Some(&nested_indent));
+            for (disk, group) in group_by_disk(&entries) {
+                println!("{}{}:", indent, disk);
+                for index in group {
+                    print_entry(
+                        &entries[index],
+                        &formatted_entries[index],
+                        &column_widths,
+                        bar_width,
+                        cfg,
+                        &nested_indent,
+                    );
+                }
+            }
+        }
+        false => {
+            util::print_row(header, &column_widths, Some(indent));
+            entries
+                .iter()
+                .zip(formatted_entries.iter())
+                .for_each(|(entry, cells)| {
+                    print_entry(entry, cells, &column_widths, bar_width, cfg, indent)
+                });
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::components::disks::{self, Entry, InodeUsage, Mount, MountStats};
+    use crate::config::{StorageConfig, StorageSort, UnitsConfig};
+    use bytesize::ByteSize;
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    fn example_entry(inodes: Option<InodeUsage>) -> Entry<'static> {
+        mounted_entry("/", "ext4", inodes)
+    }
+
+    fn mounted_entry(
+        mount_point: &'static str,
+        fs_type: &'static str,
+        inodes: Option<InodeUsage>,
+    ) -> Entry<'static> {
+        Entry {
+            mount_points: vec![mount_point],
+            label: None,
+            device: "/dev/sda1",
+            disk_type: "SSD",
+            fs_type,
+            used_percent: 50,
+            used: ByteSize::gb(5),
+            total: ByteSize::gb(10),
+            inodes,
+            days_until_full: None,
+            stale: false,
+        }
+    }
+
+    #[test]
+    fn test_format_entry() {
+        let entry = example_entry(Some(InodeUsage {
+            used: 300,
+            total: 400,
+        }));
+        assert_eq!(
+            disks::format_entry(&entry, false, false, &UnitsConfig::default()),
+            vec!["/", "SSD", "ext4", "50", "5.0 GB", "10.0 GB"]
         );
-        print_entry_bar(&entry, bar_width, &cfg.usage_colouring, Some(indent));
-    });
+        assert_eq!(
+            disks::format_entry(&entry, true, false, &UnitsConfig::default()),
+            vec!["/", "SSD", "ext4", "50", "5.0 GB", "10.0 GB", "75", "300", "400"]
+        );
+        assert_eq!(
+            disks::format_entry(&example_entry(None), true, false, &UnitsConfig::default())[6..],
+            ["N/A", "N/A", "N/A"]
+        );
+
+        let stale_entry = Entry {
+            stale: true,
+            ..example_entry(None)
+        };
+        assert_eq!(
+            disks::format_entry(&stale_entry, true, false, &UnitsConfig::default()),
+            vec!["/", "SSD", "ext4", "stale", "N/A", "N/A", "N/A", "N/A", "N/A"]
+        );
+    }
+
+    #[test]
+    fn test_parse_mounts() {
+        let contents = "/dev/vda1 / ext4 rw,relatime 0 0
+proc /proc proc rw,nosuid,nodev,noexec,relatime 0 0
+tmpfs /run tmpfs rw,nosuid,nodev 0 0
+/dev/sdb1 /run/media/usb vfat rw 0 0
+nas:/export/media /mnt/nas\\040media nfs4 rw,relatime 0 0
+sunrpc /var/lib/nfs/rpc_pipefs rpc_pipefs rw,relatime 0 0
+";
+        let mounts = disks::parse_mounts(contents);
+        assert_eq!(mounts.len(), 6);
+        assert_eq!(
+            mounts[4],
+            Mount {
+                device: "nas:/export/media".to_string(),
+                mount_point: "/mnt/nas media".to_string(),
+                fs_type: "nfs4".to_string(),
+            }
+        );
+
+        // Pseudo filesystems and system mounts are excluded by default, along with any configured exclusions.
+        let cfg = serde_json::from_str(
+            r#"{
+                "usage_colouring": { "default_colour": "Green", "levels": [] },
+                "exclude_prefixes": []
+            }"#,
+        )
+        .unwrap();
+        let included = |cfg: &StorageConfig| {
+            mounts
+                .iter()
+                .filter(|mount| {
+                    disks::is_included(&mount.mount_point, &mount.device, &mount.fs_type, cfg)
+                })
+                .map(|mount| mount.mount_point.as_str())
+                .collect::<Vec<&str>>()
+        };
+        assert_eq!(
+            included(&cfg),
+            vec!["/", "/run/media/usb", "/mnt/nas media"]
+        );
+        assert!(!disks::is_included(
+            "/run/user/1000/doc",
+            "portal",
+            "fuse",
+            &cfg
+        ));
+        assert!(!disks::is_included("/sys/fs/bpf", "bpf", "bpf", &cfg));
+        assert!(disks::is_included("/system", "/dev/sdb1", "ext4", &cfg));
+
+        let cfg = serde_json::from_str(
+            r#"{
+                "usage_colouring": { "default_colour": "Green", "levels": [] },
+                "exclude_prefixes": [],
+                "exclude": { "fs_types": ["nfs4"] }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(included(&cfg), vec!["/", "/run/media/usb"]);
+    }
+
+    #[test]
+    fn test_stat_mounts() {
+        let mount = |mount_point: &str| Mount {
+            device: "/dev/vda1".to_string(),
+            mount_point: mount_point.to_string(),
+            fs_type: "ext4".to_string(),
+        };
+        let mounts = [mount("/"), mount("/nonexistent/mount/point")];
+        let stats = disks::stat_mounts(&mounts, Duration::from_secs(10));
+        assert!(matches!(stats[0], MountStats::Ok(_)));
+        assert!(matches!(stats[1], MountStats::Failed));
+
+        assert!(disks::entry(&mounts[1], MountStats::Failed, false).is_none());
+        let stale_entry = disks::entry(&mounts[1], MountStats::Stale, false).unwrap();
+        assert!(stale_entry.stale);
+        assert_eq!(stale_entry.used_ratio(), 0.0);
+    }
+
+    #[test]
+    fn test_parse_mountinfo() {
+        let contents = "28 1 253:0 / / rw,relatime shared:1 - ext4 /dev/vda rw
+95 28 253:0 /srv/data /mnt/with\\040space rw,relatime shared:1 - ext4 /dev/vda rw
+96 28 0:45 / /var/lib/docker/overlay2/abc/merged rw,relatime - overlay overlay rw";
+        let mountinfo = disks::parse_mountinfo(contents);
+        assert_eq!(mountinfo.get("/"), Some(&"253:0".to_string()));
+        assert_eq!(mountinfo.get("/mnt/with space"), Some(&"253:0".to_string()));
+        assert_eq!(
+            mountinfo.get("/var/lib/docker/overlay2/abc/merged"),
+            Some(&"0:45".to_string())
+        );
+        assert_eq!(disks::unescape_mount_point("/a\\134b\\x"), "/a\\b\\x");
+    }
+
+    #[test]
+    fn test_deduplicate() {
+        let entries = vec![
+            mounted_entry("/srv/bind", "ext4", None),
+            mounted_entry("/", "ext4", None),
+            mounted_entry("/boot", "vfat", None),
+            mounted_entry("/merged/a", "overlay", None),
+            mounted_entry("/merged/b", "overlay", None),
+        ];
+        let mountinfo = [("/srv/bind", "253:0"), ("/", "253:0"), ("/boot", "253:1")]
+            .iter()
+            .map(|(mount_point, id)| (mount_point.to_string(), id.to_string()))
+            .collect::<HashMap<String, String>>();
+
+        let deduplicated = disks::deduplicate(entries, &mountinfo);
+        assert_eq!(
+            deduplicated
+                .iter()
+                .map(|entry| entry.mount_points.clone())
+                .collect::<Vec<Vec<&str>>>(),
+            vec![
+                vec!["/", "/srv/bind"],
+                vec!["/boot"],
+                vec!["/merged/a", "/merged/b"]
+            ]
+        );
+    }
+
+    #[test]
+    fn test_is_included() {
+        let cfg = serde_json::from_str(
+            r#"{
+                "usage_colouring": { "default_colour": "Green", "levels": [] },
+                "exclude_prefixes": ["/var/lib/docker/"],
+                "exclude": {
+                    "fs_types": ["squashfs"],
+                    "devices": ["/dev/loop*"],
+                    "mount_globs": ["/run/user/*"],
+                    "mount_regexes": ["^/var/lib/(kubelet|containers)/"]
+                },
+                "default_exclude": false
+            }"#,
+        )
+        .unwrap();
+        assert!(disks::is_included("/", "/dev/sda1", "ext4", &cfg));
+        assert!(!disks::is_included(
+            "/var/lib/docker/a",
+            "/dev/sda1",
+            "ext4",
+            &cfg
+        ));
+        assert!(!disks::is_included(
+            "/snap/core/1",
+            "/dev/sda2",
+            "squashfs",
+            &cfg
+        ));
+        assert!(!disks::is_included(
+            "/mnt/image",
+            "/dev/loop3",
+            "ext4",
+            &cfg
+        ));
+        assert!(!disks::is_included(
+            "/run/user/1000",
+            "tmpfs",
+            "tmpfs",
+            &cfg
+        ));
+        assert!(disks::is_included(
+            "/run/user/1000/doc",
+            "portal",
+            "fuse",
+            &cfg
+        ));
+        assert!(!disks::is_included(
+            "/var/lib/kubelet/pods",
+            "/dev/sda1",
+            "ext4",
+            &cfg
+        ));
+
+        let cfg = serde_json::from_str(
+            r#"{
+                "usage_colouring": { "default_colour": "Green", "levels": [] },
+                "exclude_prefixes": [],
+                "include": { "fs_types": ["ext4", "tmpfs"] },
+                "exclude": { "mount_globs": ["/run/**"] },
+                "default_exclude": false,
+                "labels": { "/": "System", "/srv": "Data", "/dev/shm": "Shared" },
+                "only_labelled": true
+            }"#,
+        )
+        .unwrap();
+        assert!(disks::is_included("/", "/dev/sda1", "ext4", &cfg));
+        assert!(disks::is_included("/dev/shm", "tmpfs", "tmpfs", &cfg));
+        assert!(!disks::is_included("/srv", "/dev/sdb1", "xfs", &cfg));
+        assert!(!disks::is_included("/home", "/dev/sda3", "ext4", &cfg));
+    }
+
+    #[test]
+    fn test_format_days_until_full() {
+        let entry = Entry {
+            days_until_full: Some(12.4),
+            ..example_entry(None)
+        };
+        assert_eq!(
+            disks::format_entry(&entry, false, true, &UnitsConfig::default())[6],
+            "~12 days"
+        );
+        assert_eq!(disks::format_days_until_full(Some(0.2)), "<1 day");
+        assert_eq!(disks::format_days_until_full(Some(1.2)), "~1 day");
+        assert_eq!(disks::format_days_until_full(None), "-");
+    }
+
+    #[test]
+    fn test_sort_entries() {
+        let sized_entry = |mount_point, used, total| Entry {
+            used: ByteSize::gb(used),
+            total: ByteSize::gb(total),
+            ..mounted_entry(mount_point, "ext4", None)
+        };
+        let mut entries = vec![
+            sized_entry("/srv", 900, 1000),
+            sized_entry("/", 20, 32),
+            sized_entry("/home", 10, 100),
+        ];
+        fn mount_points(entries: &[Entry<'static>]) -> Vec<&'static str> {
+            entries.iter().map(|entry| entry.mount_points[0]).collect()
+        }
+
+        disks::sort_entries(&mut entries, &StorageSort::Mount);
+        assert_eq!(mount_points(&entries), vec!["/", "/home", "/srv"]);
+        disks::sort_entries(&mut entries, &StorageSort::UsedPercent);
+        assert_eq!(mount_points(&entries), vec!["/srv", "/", "/home"]);
+        disks::sort_entries(&mut entries, &StorageSort::Free);
+        assert_eq!(mount_points(&entries), vec!["/", "/home", "/srv"]);
+    }
 }
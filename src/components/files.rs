@@ -0,0 +1,337 @@
+use crate::config::{FileCheckConfig, FilesConfig, TimeFormatConfig, UnitsConfig};
+use crate::time_format;
+use crate::units;
+use crate::util;
+use colored::Color;
+use std::fs;
+use std::path::Path;
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+#[derive(Debug, PartialEq)]
+struct FileInfo {
+    name: String,
+    modified: SystemTime,
+    size: u64,
+}
+
+#[derive(Debug, PartialEq)]
+enum Matched {
+    /// Nothing matches the path.
+    Missing,
+    /// Only empty directories match the path.
+    Empty,
+    /// The most recently modified file matching the path or inside a matching directory.
+    Newest(FileInfo),
+    /// The check did not finish in time e.g. the path is on an unreachable network filesystem.
+    Stale,
+}
+
+fn file_info(path: &Path, metadata: &fs::Metadata) -> Option<FileInfo> {
+    Some(FileInfo {
+        name: path.file_name()?.to_string_lossy().to_string(),
+        modified: metadata.modified().ok()?,
+        size: metadata.len(),
+    })
+}
+
+/// Find the newest file matching a path or glob pattern. Matching directories are replaced by the files directly inside them.
+fn newest_file(pattern: &str) -> Matched {
+    let paths = match glob::glob(pattern) {
+        Ok(paths) => paths.filter_map(|path| path.ok()).collect::<Vec<_>>(),
+        Err(_) => return Matched::Missing,
+    };
+    if paths.is_empty() {
+        return Matched::Missing;
+    }
+
+    let mut files = Vec::new();
+    for path in paths {
+        match fs::metadata(&path) {
+            Ok(metadata) if metadata.is_dir() => {
+                let dir = match fs::read_dir(&path) {
+                    Ok(dir) => dir,
+                    Err(_) => continue,
+                };
+                files.extend(dir.filter_map(|entry| {
+                    let path = entry.ok()?.path();
+                    let metadata = fs::metadata(&path).ok()?;
+                    match metadata.is_file() {
+                        true => file_info(&path, &metadata),
+                        false => None,
+                    }
+                }));
+            }
+            Ok(metadata) => files.extend(file_info(&path, &metadata)),
+            Err(_) => {}
+        }
+    }
+
+    match files.into_iter().max_by_key(|file| file.modified) {
+        Some(file) => Matched::Newest(file),
+        None => Matched::Empty,
+    }
+}
+
+/// Run each check in its own worker thread so that a hung network filesystem cannot block the report.
+///
+/// Checks which have not finished within `timeout` of the workers being started are stale.
+/// Their workers are left blocked in the background and end with the process.
+fn check_files(checks: &[FileCheckConfig], timeout: Duration) -> Vec<Matched> {
+    let deadline = Instant::now() + timeout;
+    let receivers = checks
+        .iter()
+        .map(|check| {
+            let (sender, receiver) = mpsc::channel();
+            let pattern = check.path.clone();
+            thread::spawn(move || sender.send(newest_file(&pattern)));
+            receiver
+        })
+        .collect::<Vec<_>>();
+
+    receivers
+        .into_iter()
+        .map(|receiver| {
+            receiver
+                .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+                .unwrap_or(Matched::Stale)
+        })
+        .collect()
+}
+
+#[derive(Debug, PartialEq)]
+struct Entry {
+    name: String,
+    newest: Option<String>,
+    /// Seconds since the newest file was modified.
+    age: Option<u64>,
+    size: Option<u64>,
+    /// Expectations from the config which are not met. The check passed if this is empty.
+    problems: Vec<&'static str>,
+}
+
+fn entry(cfg: &FileCheckConfig, matched: Matched, now: SystemTime) -> Entry {
+    let name = cfg.label.clone().unwrap_or_else(|| cfg.path.clone());
+    let file = match matched {
+        Matched::Newest(file) => file,
+        matched => {
+            let problem = match (matched, cfg.exists) {
+                (Matched::Stale, _) => Some("Stale"),
+                (_, false) => None,
+                (Matched::Empty, true) => Some("No files"),
+                (_, true) => Some("Missing"),
+            };
+            return Entry {
+                name,
+                newest: None,
+                age: None,
+                size: None,
+                problems: problem.into_iter().collect(),
+            };
+        }
+    };
+
+    let age = now
+        .duration_since(file.modified)
+        .map(|age| age.as_secs())
+        .unwrap_or(0);
+    let mut problems = Vec::new();
+    if !cfg.exists {
+        problems.push("Should not exist");
+    }
+    if cfg.max_age.is_some_and(|max_age| age > max_age) {
+        problems.push("Too old");
+    }
+    if cfg.min_size.is_some_and(|min_size| file.size < min_size) {
+        problems.push("Too small");
+    }
+    if cfg.max_size.is_some_and(|max_size| file.size > max_size) {
+        problems.push("Too large");
+    }
+
+    Entry {
+        name,
+        newest: Some(file.name),
+        age: Some(age),
+        size: Some(file.size),
+        problems,
+    }
+}
+
+fn age_colour(entry: &Entry, cfg: &FileCheckConfig) -> Option<Color> {
+    match (&cfg.age, entry.age) {
+        (Some(cond_colour), Some(age)) => Some(util::select_colour_number(age, cond_colour)),
+        (None, Some(_)) if entry.problems.contains(&"Too old") => Some(Color::Red),
+        _ => None,
+    }
+}
+
+pub fn print_files(
+    cfg: &FilesConfig,
+    time_cfg: &TimeFormatConfig,
+    units_cfg: &UnitsConfig,
+    indent: &str,
+) {
+    let header = ["File", "Newest", "Age", "Size", "Status"];
+
+    let now = SystemTime::now();
+    let entries = cfg
+        .checks
+        .iter()
+        .zip(check_files(
+            &cfg.checks,
+            Duration::from_millis(cfg.timeout_ms),
+        ))
+        .map(|(check, matched)| entry(check, matched, now))
+        .collect::<Vec<Entry>>();
+
+    let formatted_entries = entries
+        .iter()
+        .map(|entry| {
+            [
+                entry.name.clone(),
+                entry.newest.clone().unwrap_or_else(|| "-".to_string()),
+                entry
+                    .age
+                    .map(|age| time_format::format_duration(age, time_cfg.max_units))
+                    .unwrap_or_else(|| "-".to_string()),
+                entry
+                    .size
+                    .map(|size| units::format_bytes(size, &units_cfg.bytes))
+                    .unwrap_or_else(|| "-".to_string()),
+                match entry.problems.is_empty() {
+                    true => "OK".to_string(),
+                    false => entry.problems.join(", "),
+                },
+            ]
+        })
+        .collect::<Vec<[String; 5]>>();
+
+    let column_widths = util::column_widths(
+        &header,
+        formatted_entries
+            .iter()
+            .map(|cells| cells.iter().map(|cell| cell.chars().count()).collect()),
+    );
+
+    println!("Files:");
+    util::print_row(header, &column_widths, Some(indent));
+    entries
+        .iter()
+        .zip(cfg.checks.iter())
+        .zip(formatted_entries.iter())
+        .for_each(|((entry, check), cells)| {
+            let formatted_cells =
+                util::format_width(cells.iter().map(|cell| cell.as_str()), &column_widths);
+            let size_problem = entry
+                .problems
+                .iter()
+                .any(|problem| *problem == "Too small" || *problem == "Too large");
+            let colours = [
+                None,
+                None,
+                age_colour(entry, check),
+                size_problem.then_some(Color::Red),
+                Some(match entry.problems.is_empty() {
+                    true => Color::Green,
+                    false => Color::Red,
+                }),
+            ];
+            util::print_row_colour(formatted_cells, colours, Some(indent))
+        });
+}
+
+#[cfg(test)]
+mod test {
+    use crate::components::files::{self, Matched};
+    use crate::config::FileCheckConfig;
+    use std::fs::{self, File};
+    use std::path::PathBuf;
+    use std::process;
+    use std::time::{Duration, SystemTime};
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("sysstatus-files-{}-{}", name, process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn write_file(path: &PathBuf, contents: &str, age: u64) {
+        fs::write(path, contents).unwrap();
+        File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(SystemTime::now() - Duration::from_secs(age))
+            .unwrap();
+    }
+
+    fn check(json: &str) -> FileCheckConfig {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_newest_file() {
+        let dir = temp_dir("newest");
+        write_file(&dir.join("backup-1.tar"), "old", 2 * 24 * 60 * 60);
+        write_file(&dir.join("backup-2.tar"), "newer", 60 * 60);
+        fs::create_dir(dir.join("empty")).unwrap();
+
+        let newest = |pattern: &str| files::newest_file(&dir.join(pattern).to_string_lossy());
+        match newest("") {
+            Matched::Newest(file) => {
+                assert_eq!(file.name, "backup-2.tar");
+                assert_eq!(file.size, 5);
+            }
+            matched => panic!("Expected a file, got {:?}", matched),
+        }
+        match newest("backup-1.*") {
+            Matched::Newest(file) => assert_eq!(file.name, "backup-1.tar"),
+            matched => panic!("Expected a file, got {:?}", matched),
+        }
+        assert_eq!(newest("empty"), Matched::Empty);
+        assert_eq!(newest("missing-*"), Matched::Missing);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_entry() {
+        let dir = temp_dir("entry");
+        write_file(&dir.join("db.sql.gz"), "dump", 2 * 24 * 60 * 60);
+        let path = dir.join("*.sql.gz").to_string_lossy().to_string();
+        let now = SystemTime::now();
+
+        let cfg = check(&format!(
+            r#"{{"path": "{}", "label": "Database backup", "max_age": 86400, "min_size": 1024}}"#,
+            path
+        ));
+        let entry = files::entry(&cfg, files::newest_file(&cfg.path), now);
+        assert_eq!(entry.name, "Database backup");
+        assert_eq!(entry.newest.as_deref(), Some("db.sql.gz"));
+        assert_eq!(entry.size, Some(4));
+        assert!(entry.age.unwrap() >= 2 * 24 * 60 * 60);
+        assert_eq!(entry.problems, vec!["Too old", "Too small"]);
+
+        let cfg = check(&format!(r#"{{"path": "{}", "max_age": 259200}}"#, path));
+        let entry = files::entry(&cfg, files::newest_file(&cfg.path), now);
+        assert!(entry.problems.is_empty());
+
+        let cfg = check(r#"{"path": "/run/backup.lock", "exists": false}"#);
+        let entry = files::entry(&cfg, Matched::Missing, now);
+        assert!(entry.problems.is_empty());
+        let entry = files::entry(&check(r#"{"path": "/backups"}"#), Matched::Missing, now);
+        assert_eq!(entry.problems, vec!["Missing"]);
+        let entry = files::entry(&cfg, Matched::Stale, now);
+        assert_eq!(entry.problems, vec!["Stale"]);
+
+        let checks = [check(&format!(r#"{{"path": "{}"}}"#, path)), cfg];
+        let matched = files::check_files(&checks, Duration::from_secs(10));
+        assert!(matches!(&matched[0], Matched::Newest(file) if file.name == "db.sql.gz"));
+        assert_eq!(matched[1], Matched::Missing);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
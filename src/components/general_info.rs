@@ -1,8 +1,17 @@
-use crate::config::GeneralInfoConfig;
+use crate::config::{
+    ConditionalColour, CpuCoresConfig, GeneralInfoConfig, TimeFormatConfig, UnitsConfig,
+};
+use crate::time_format;
+use crate::units;
 use crate::util::select_colour_number;
-use bytesize::ByteSize;
 use colored::*;
-use sysinfo::{CpuExt, System, SystemExt};
+use std::collections::HashMap;
+use std::fs;
+use std::thread;
+use std::time::Duration;
+use sysinfo::{Cpu, CpuExt, System, SystemExt};
+
+const ALIGN_LENGTH: usize = 9;
 
 pub fn load(sys: &mut System, cfg: &GeneralInfoConfig) -> String {
     sys.refresh_cpu();
@@ -23,7 +32,17 @@ pub fn load(sys: &mut System, cfg: &GeneralInfoConfig) -> String {
     )
 }
 
-pub fn memory(sys: &mut System, cfg: &GeneralInfoConfig) -> String {
+/// Format a size in kB, the unit sysinfo converts the KiB values of `/proc/meminfo` to.
+fn format_kb(kb: u64, units_cfg: &UnitsConfig) -> String {
+    units::format_bytes(kb * 1000, &units_cfg.bytes)
+}
+
+/// Format a size in KiB, the unit used by `/proc/meminfo` despite being written as `kB`.
+fn format_kib(kib: u64, units_cfg: &UnitsConfig) -> String {
+    units::format_bytes(kib * 1024, &units_cfg.bytes)
+}
+
+pub fn memory(sys: &mut System, cfg: &GeneralInfoConfig, units_cfg: &UnitsConfig) -> String {
     sys.refresh_memory();
     let used_mem = sys.used_memory();
     let total_mem = sys.total_memory();
@@ -32,60 +51,254 @@ pub fn memory(sys: &mut System, cfg: &GeneralInfoConfig) -> String {
     let colour = select_colour_number(used_mem as f32 / total_mem as f32, &cfg.memory);
     format!(
         "{} used, {} available, {} total",
-        ByteSize::kb(used_mem).to_string().color(colour),
-        ByteSize::kb(available_mem),
-        ByteSize::kb(total_mem)
+        format_kb(used_mem, units_cfg).color(colour),
+        format_kb(available_mem, units_cfg),
+        format_kb(total_mem, units_cfg)
+    )
+}
+
+pub fn swap(sys: &mut System, cfg: &ConditionalColour<f32>, units_cfg: &UnitsConfig) -> String {
+    let used_swap = sys.used_swap();
+    let total_swap = sys.total_swap();
+    if total_swap == 0 {
+        return "None".to_string();
+    }
+
+    let colour = select_colour_number(used_swap as f32 / total_swap as f32, cfg);
+    format!(
+        "{} used, {} total",
+        format_kb(used_swap, units_cfg).color(colour),
+        format_kb(total_swap, units_cfg)
     )
 }
 
-pub fn cpu(sys: &mut System, cfg: &GeneralInfoConfig) -> String {
+/// Parse the contents of `/proc/meminfo` into a map from field name to value.
+/// Values are left in the unit used by the file, which is kB for most fields and a count for `HugePages_*` fields.
+fn parse_meminfo(contents: &str) -> HashMap<&str, u64> {
+    contents
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            let value = value.split_whitespace().next()?.parse::<u64>().ok()?;
+            Some((key, value))
+        })
+        .collect()
+}
+
+fn format_memory_breakdown(meminfo: &HashMap<&str, u64>, units_cfg: &UnitsConfig) -> String {
+    let field = |key: &str| meminfo.get(key).copied().unwrap_or(0);
+    let buff_cache = field("Buffers") + field("Cached") + field("SReclaimable");
+    let mut breakdown = format!(
+        "{} buffers/cache, {} shared",
+        format_kib(buff_cache, units_cfg),
+        format_kib(field("Shmem"), units_cfg)
+    );
+
+    let huge_pages_total = field("HugePages_Total");
+    if huge_pages_total > 0 {
+        breakdown += &format!(
+            ", {}/{} huge pages used ({} each)",
+            huge_pages_total - field("HugePages_Free"),
+            huge_pages_total,
+            format_kib(field("Hugepagesize"), units_cfg)
+        );
+    }
+    breakdown
+}
+
+pub fn memory_breakdown(units_cfg: &UnitsConfig) -> String {
+    match fs::read_to_string("/proc/meminfo") {
+        Ok(contents) => format_memory_breakdown(&parse_meminfo(&contents), units_cfg),
+        Err(_) => "N/A".to_string(),
+    }
+}
+
+pub fn cpu(sys: &mut System, cfg: &GeneralInfoConfig, units_cfg: &UnitsConfig) -> String {
     sys.refresh_cpu();
     let processors = sys.cpus();
-    let freq_colour = select_colour_number(processors[0].frequency(), &cfg.cpu_frequency);
+    let frequency = processors[0].frequency();
+    let freq_colour = select_colour_number(
+        units::convert_frequency(frequency, &units_cfg.frequency),
+        &cfg.cpu_frequency,
+    );
     format!(
-        "{} - {} MHz",
+        "{} - {} {}",
         processors[0].brand(),
-        processors[0].frequency().to_string().color(freq_colour)
+        units::frequency_value(frequency, &units_cfg.frequency).color(freq_colour),
+        units::frequency_symbol(&units_cfg.frequency)
     )
 }
 
-pub fn uptime(sys: &mut System) -> String {
-    let uptime = sys.uptime();
-    let seconds = uptime % 60;
-    let minutes = (uptime / 60) % 60;
-    let hours = (uptime / (60 * 60)) % 24;
-    let days = (uptime / (24 * 60 * 60)) % 7;
-    let weeks = uptime / (24 * 60 * 60 * 7);
+fn format_core(
+    core: &Cpu,
+    cfg: &GeneralInfoConfig,
+    cores_cfg: &CpuCoresConfig,
+    units_cfg: &UnitsConfig,
+    name_width: usize,
+) -> String {
+    let usage = core.cpu_usage();
+    let usage_colour = select_colour_number(usage, &cores_cfg.usage);
+    let used_bar_width =
+        ((usage / 100.0 * cores_cfg.bar_width as f32) as usize).min(cores_cfg.bar_width);
     format!(
-        "{} weeks, {} days, {} hours, {} minutes, {} seconds",
-        weeks, days, hours, minutes, seconds
+        "{:<name_width$} [{}{}] {} {} {}",
+        core.name(),
+        "=".repeat(used_bar_width).color(usage_colour),
+        " ".repeat(cores_cfg.bar_width - used_bar_width),
+        format!("{:>5.1}%", usage).color(usage_colour),
+        format!(
+            "{:>4}",
+            units::frequency_value(core.frequency(), &units_cfg.frequency)
+        )
+        .color(select_colour_number(
+            units::convert_frequency(core.frequency(), &units_cfg.frequency),
+            &cfg.cpu_frequency
+        )),
+        units::frequency_symbol(&units_cfg.frequency),
+        name_width = name_width
     )
 }
 
-pub fn general_info(sys: &mut System, cfg: &GeneralInfoConfig) -> String {
+/// Sample the CPU usage and return the overall usage followed by lines showing the usage and frequency of each core.
+pub fn cpu_cores(
+    sys: &mut System,
+    cfg: &GeneralInfoConfig,
+    cores_cfg: &CpuCoresConfig,
+    units_cfg: &UnitsConfig,
+) -> (String, Vec<String>) {
+    // Usage is calculated from the difference between two refreshes.
+    sys.refresh_cpu();
+    thread::sleep(Duration::from_millis(cores_cfg.sample_interval_ms));
+    sys.refresh_cpu();
+
+    let overall_usage = sys.global_cpu_info().cpu_usage();
+    let overall = format!("{:.1}%", overall_usage)
+        .color(select_colour_number(overall_usage, &cores_cfg.usage))
+        .to_string();
+
+    let name_width = sys
+        .cpus()
+        .iter()
+        .map(|core| core.name().len())
+        .max()
+        .unwrap_or(0);
+    let cores = sys
+        .cpus()
+        .chunks(cores_cfg.columns.max(1))
+        .map(|chunk| {
+            chunk
+                .iter()
+                .map(|core| format_core(core, cfg, cores_cfg, units_cfg, name_width))
+                .collect::<Vec<String>>()
+                .join("  ")
+        })
+        .collect();
+    (overall, cores)
+}
+
+pub fn uptime(sys: &mut System, time_cfg: &TimeFormatConfig) -> String {
+    time_format::format_duration(sys.uptime(), time_cfg.max_units)
+}
+
+fn row(label: &str, value: String) -> String {
+    format!("    {:.<max$}: {}", label, value, max = ALIGN_LENGTH)
+}
+
+pub fn general_info(
+    sys: &mut System,
+    cfg: &GeneralInfoConfig,
+    time_cfg: &TimeFormatConfig,
+    units_cfg: &UnitsConfig,
+) -> String {
+    let mut memory_rows = vec![row("Memory", memory(sys, cfg, units_cfg))];
+    if let Some(swap_cfg) = &cfg.swap {
+        memory_rows.push(row("Swap", swap(sys, swap_cfg, units_cfg)));
+    }
+    if cfg.memory_breakdown {
+        memory_rows.push(row("Breakdown", memory_breakdown(units_cfg)));
+    }
+    memory_rows.push(row("CPU", cpu(sys, cfg, units_cfg)));
+    if let Some(cores_cfg) = &cfg.cpu_cores {
+        let (overall, cores) = cpu_cores(sys, cfg, cores_cfg, units_cfg);
+        memory_rows.push(row("CPU Usage", overall));
+        memory_rows.extend(cores.into_iter().map(|line| format!("        {}", line)));
+    }
+
+    let groups = [
+        vec![
+            row("OS", sys.long_os_version().unwrap_or("N/A".to_string())),
+            row("Kernel", sys.kernel_version().unwrap_or("N/A".to_string())),
+        ],
+        vec![
+            row("Uptime", uptime(sys, time_cfg)),
+            row("Load", load(sys, cfg)),
+        ],
+        memory_rows,
+    ];
+
     format!(
-        "General:
-    {:.<max$}: {}
-    {:.<max$}: {}
-
-    {:.<max$}: {}
-    {:.<max$}: {}
-
-    {:.<max$}: {}
-    {:.<max$}: {}
-    ",
-        "OS",
-        sys.long_os_version().unwrap_or("N/A".to_string()),
-        "Kernel",
-        sys.kernel_version().unwrap_or("N/A".to_string()),
-        "Uptime",
-        uptime(sys),
-        "Load",
-        load(sys, cfg),
-        "Memory",
-        memory(sys, cfg),
-        "CPU",
-        cpu(sys, cfg),
-        max = 9
+        "General:\n{}\n    ",
+        groups
+            .iter()
+            .map(|group| group.join("\n"))
+            .collect::<Vec<String>>()
+            .join("\n\n")
     )
 }
+
+#[cfg(test)]
+mod test {
+    use crate::components::general_info;
+    use crate::config::{ByteUnit, UnitsConfig};
+
+    const MEMINFO: &str = "MemTotal:        6158152 kB
+MemFree:         3872884 kB
+Buffers:           65072 kB
+Cached:          1905712 kB
+Shmem:              9484 kB
+SReclaimable:      52588 kB
+HugePages_Total:       4
+HugePages_Free:        1
+Hugepagesize:       2048 kB
+";
+
+    #[test]
+    fn test_parse_meminfo() {
+        let meminfo = general_info::parse_meminfo(MEMINFO);
+        assert_eq!(meminfo.get("MemTotal"), Some(&6158152));
+        assert_eq!(meminfo.get("HugePages_Total"), Some(&4));
+        assert_eq!(meminfo.get("Missing"), None);
+    }
+
+    #[test]
+    fn test_format_memory_breakdown() {
+        let meminfo = general_info::parse_meminfo(MEMINFO);
+        assert_eq!(
+            general_info::format_memory_breakdown(&meminfo, &UnitsConfig::default()),
+            "2.1 GB buffers/cache, 9.7 MB shared, 3/4 huge pages used (2.1 MB each)"
+        );
+        let iec_units = UnitsConfig {
+            bytes: ByteUnit::Iec,
+            ..UnitsConfig::default()
+        };
+        assert_eq!(
+            general_info::format_memory_breakdown(&meminfo, &iec_units),
+            "1.9 GiB buffers/cache, 9.3 MiB shared, 3/4 huge pages used (2.0 MiB each)"
+        );
+    }
+
+    #[test]
+    fn test_format_kb() {
+        // sysinfo reports the 6158152 KiB of MemTotal as 6158152 * 128 / 125 kB.
+        let total_memory = 6305947;
+        assert_eq!(
+            general_info::format_kb(total_memory, &UnitsConfig::default()),
+            "6.3 GB"
+        );
+        let iec_units = UnitsConfig {
+            bytes: ByteUnit::Iec,
+            ..UnitsConfig::default()
+        };
+        assert_eq!(general_info::format_kb(total_memory, &iec_units), "5.9 GiB");
+    }
+}
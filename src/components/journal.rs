@@ -0,0 +1,189 @@
+use crate::command;
+use crate::config::{JournalConfig, TimeFormatConfig};
+use crate::time_format;
+use crate::util;
+use chrono::{DateTime, TimeZone, Utc};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::process::Command;
+use std::time::Duration;
+
+#[derive(Debug, PartialEq)]
+struct Entry {
+    /// Systemd unit, or syslog identifier for messages not from a unit e.g. `kernel`.
+    unit: String,
+    count: u64,
+    latest: Option<DateTime<Utc>>,
+    /// First line of the latest message.
+    message: String,
+}
+
+fn journal_output(since: &str, timeout_ms: u64) -> Result<String, String> {
+    let mut command = Command::new("journalctl");
+    command
+        .args(["--output=json", "--priority=err", "--no-pager", "--quiet"])
+        .arg(format!("--since={}", since));
+    let output = command::output_with_timeout(&mut command, Duration::from_millis(timeout_ms))
+        .map_err(|e| format!("Failed to run journalctl: {}", e))?
+        .ok_or_else(|| format!("journalctl timed out after {} ms", timeout_ms))?;
+    match output.status.success() {
+        true => Ok(String::from_utf8_lossy(&output.stdout).to_string()),
+        false => Err(String::from_utf8_lossy(&output.stderr).trim().to_string()),
+    }
+}
+
+/// journalctl writes fields which are not valid UTF-8 as an array of bytes.
+fn field_string(value: &Value) -> Option<String> {
+    match value {
+        Value::String(string) => Some(string.clone()),
+        Value::Array(bytes) => {
+            let bytes = bytes
+                .iter()
+                .map(|byte| byte.as_u64().map(|byte| byte as u8))
+                .collect::<Option<Vec<u8>>>()?;
+            Some(String::from_utf8_lossy(&bytes).to_string())
+        }
+        _ => None,
+    }
+}
+
+/// Group the entries output by `journalctl --output=json` by unit, most errors first.
+fn parse_journal(output: &str) -> Vec<Entry> {
+    let mut entries: HashMap<String, Entry> = HashMap::new();
+    for record in output
+        .lines()
+        .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+    {
+        let field = |name| record.get(name).and_then(field_string);
+        let unit = field("_SYSTEMD_UNIT")
+            .or_else(|| field("SYSLOG_IDENTIFIER"))
+            .or_else(|| field("_COMM"))
+            .unwrap_or_else(|| "unknown".to_string());
+        let time = field("__REALTIME_TIMESTAMP")
+            .and_then(|micros| micros.parse::<i64>().ok())
+            .and_then(|micros| Utc.timestamp_opt(micros / 1_000_000, 0).single());
+        let message = field("MESSAGE")
+            .and_then(|message| message.lines().next().map(|line| line.to_string()))
+            .unwrap_or_default();
+
+        let entry = entries.entry(unit.clone()).or_insert(Entry {
+            unit,
+            count: 0,
+            latest: None,
+            message: String::new(),
+        });
+        entry.count += 1;
+        if entry.latest.is_none() || time >= entry.latest {
+            entry.latest = time;
+            entry.message = message;
+        }
+    }
+
+    let mut entries = entries.into_values().collect::<Vec<Entry>>();
+    entries.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.unit.cmp(&b.unit)));
+    entries
+}
+
+fn truncate(message: &str, width: usize) -> String {
+    match message.chars().count() > width {
+        true => format!(
+            "{}…",
+            message
+                .chars()
+                .take(width.saturating_sub(1))
+                .collect::<String>()
+        ),
+        false => message.to_string(),
+    }
+}
+
+pub fn print_journal(cfg: &JournalConfig, time_cfg: &TimeFormatConfig, indent: &str) {
+    let header = ["Unit", "Errors", "Latest", "Message"];
+
+    println!("Journal errors:");
+    let mut entries = match journal_output(&cfg.since, cfg.timeout_ms) {
+        Ok(output) => parse_journal(&output),
+        Err(e) => {
+            println!("{}{}", indent, e);
+            return;
+        }
+    };
+    if entries.is_empty() {
+        println!("{}No errors since {}", indent, cfg.since);
+        return;
+    }
+    if let Some(max_units) = cfg.max_units {
+        entries.truncate(max_units);
+    }
+
+    let now = Utc::now();
+    let formatted_entries = entries
+        .iter()
+        .map(|entry| {
+            [
+                entry.unit.clone(),
+                entry.count.to_string(),
+                entry
+                    .latest
+                    .map(|latest| time_format::format_time(&latest, &now, time_cfg))
+                    .unwrap_or_else(|| "-".to_string()),
+                truncate(&entry.message, cfg.message_width),
+            ]
+        })
+        .collect::<Vec<[String; 4]>>();
+
+    let column_widths = util::column_widths(
+        &header,
+        formatted_entries
+            .iter()
+            .map(|cells| cells.iter().map(|cell| cell.chars().count()).collect()),
+    );
+
+    util::print_row(header, &column_widths, Some(indent));
+    entries
+        .iter()
+        .zip(formatted_entries.iter())
+        .for_each(|(entry, cells)| {
+            let formatted_cells =
+                util::format_width(cells.iter().map(|cell| cell.as_str()), &column_widths);
+            let colours = [
+                None,
+                Some(util::select_colour_number(entry.count, &cfg.count)),
+                None,
+                None,
+            ];
+            util::print_row_colour(formatted_cells, colours, Some(indent))
+        });
+}
+
+#[cfg(test)]
+mod test {
+    use crate::components::journal;
+    use chrono::{TimeZone, Utc};
+
+    #[test]
+    fn test_parse_journal() {
+        let output = r#"{"__REALTIME_TIMESTAMP":"1700000000000000","PRIORITY":"3","_SYSTEMD_UNIT":"backup.service","SYSLOG_IDENTIFIER":"restic","MESSAGE":"Fatal: unable to open repository"}
+{"__REALTIME_TIMESTAMP":"1700000060000000","PRIORITY":"3","_TRANSPORT":"kernel","SYSLOG_IDENTIFIER":"kernel","MESSAGE":"EXT4-fs error (device sda1): bad block"}
+{"__REALTIME_TIMESTAMP":"1700000120000000","PRIORITY":"2","_SYSTEMD_UNIT":"backup.service","MESSAGE":[70,97,105,108,101,100,10,115,101,99,111,110,100]}
+not json
+"#;
+        let entries = journal::parse_journal(output);
+        assert_eq!(entries.len(), 2);
+
+        assert_eq!(entries[0].unit, "backup.service");
+        assert_eq!(entries[0].count, 2);
+        assert_eq!(entries[0].latest, Utc.timestamp_opt(1700000120, 0).single());
+        assert_eq!(entries[0].message, "Failed");
+
+        assert_eq!(entries[1].unit, "kernel");
+        assert_eq!(entries[1].count, 1);
+        assert_eq!(entries[1].message, "EXT4-fs error (device sda1): bad block");
+    }
+
+    #[test]
+    fn test_truncate() {
+        assert_eq!(journal::truncate("short", 10), "short");
+        assert_eq!(journal::truncate("a longer message", 10), "a longer …");
+    }
+}
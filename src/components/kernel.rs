@@ -0,0 +1,285 @@
+use crate::config::{KernelConfig, TimeFormatConfig};
+use crate::time_format;
+use crate::util;
+use chrono::{Duration, Utc};
+use lazy_regex::regex;
+use std::fs::OpenOptions;
+use std::io::{self, Read};
+use std::mem;
+use std::os::unix::fs::OpenOptionsExt;
+use std::process::Command;
+
+/// Kernel events reported in the order they are shown.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Event {
+    OomKill,
+    Segfault,
+    HungTask,
+    IoError,
+}
+
+const EVENTS: [Event; 4] = [
+    Event::OomKill,
+    Event::Segfault,
+    Event::HungTask,
+    Event::IoError,
+];
+
+impl Event {
+    fn label(&self) -> &'static str {
+        match self {
+            Event::OomKill => "OOM kills",
+            Event::Segfault => "Segfaults",
+            Event::HungTask => "Hung tasks",
+            Event::IoError => "I/O errors",
+        }
+    }
+}
+
+/// Match a kernel message to an event along with the process or device it concerns.
+fn classify(message: &str) -> Option<(Event, String)> {
+    let oom_regex = regex!(r"Killed process \d+ \(([^)]+)\)");
+    let segfault_regex = regex!(r"^(\S+)\[\d+\]: segfault at");
+    let hung_task_regex = regex!(r"INFO: task (\S+):\d+ blocked for more than");
+    let io_error_regex = regex!(r"I/O error,? (?:on )?dev (\w+)");
+
+    [
+        (Event::OomKill, oom_regex),
+        (Event::Segfault, segfault_regex),
+        (Event::HungTask, hung_task_regex),
+        (Event::IoError, io_error_regex),
+    ]
+    .iter()
+    .find_map(|(event, regex)| Some((*event, regex.captures(message)?[1].to_string())))
+}
+
+/// Parse a record read from `/dev/kmsg` e.g. `3,1024,51234567,-;message` into seconds since boot and the message.
+fn parse_kmsg_record(record: &str) -> Option<(f64, String)> {
+    let (header, message) = record.split_once(';')?;
+    let micros = header.split(',').nth(2)?.parse::<u64>().ok()?;
+    // Continuation lines of dictionary properties follow the first line of the message.
+    let message = message.lines().next()?;
+    Some((micros as f64 / 1_000_000.0, message.to_string()))
+}
+
+/// Parse a line output by `dmesg` e.g. `[   51.234567] message` into seconds since boot and the message.
+fn parse_dmesg_line(line: &str) -> Option<(f64, String)> {
+    let captures = regex!(r"^\[\s*(\d+\.\d+)\]\s?(.*)$").captures(line)?;
+    Some((captures[1].parse().ok()?, captures[2].to_string()))
+}
+
+/// Read the kernel ring buffer from `/dev/kmsg` without blocking once all records have been read.
+fn read_kmsg() -> io::Result<Vec<(f64, String)>> {
+    let mut file = OpenOptions::new()
+        .read(true)
+        .custom_flags(libc::O_NONBLOCK)
+        .open("/dev/kmsg")?;
+    let mut buffer = vec![0; 8192];
+    let mut records = Vec::new();
+    loop {
+        match file.read(&mut buffer) {
+            Ok(0) => return Ok(records),
+            Ok(length) => records.extend(parse_kmsg_record(&String::from_utf8_lossy(
+                &buffer[..length],
+            ))),
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => return Ok(records),
+            // Records were overwritten while reading, the next read continues from the oldest remaining record.
+            Err(e) if e.raw_os_error() == Some(libc::EPIPE) => continue,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+fn read_dmesg() -> Option<Vec<(f64, String)>> {
+    let output = Command::new("dmesg").output().ok()?;
+    match output.status.success() {
+        true => Some(
+            String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .filter_map(parse_dmesg_line)
+                .collect(),
+        ),
+        false => None,
+    }
+}
+
+/// Seconds since boot on the same clock as kernel message timestamps. Unlike `/proc/uptime`, `CLOCK_MONOTONIC`
+/// does not advance while the system is suspended.
+fn monotonic_seconds() -> Option<f64> {
+    // SAFETY: `timespec` is a plain C struct for which all zero bytes is a valid value.
+    let mut time: libc::timespec = unsafe { mem::zeroed() };
+    // SAFETY: `time` is a valid pointer for the duration of the call.
+    if unsafe { libc::clock_gettime(libc::CLOCK_MONOTONIC, &mut time) } != 0 {
+        return None;
+    }
+    Some(time.tv_sec as f64 + time.tv_nsec as f64 / 1_000_000_000.0)
+}
+
+#[derive(Debug, PartialEq)]
+struct Entry {
+    event: Event,
+    count: u64,
+    /// Seconds since boot of the latest occurrence.
+    latest: Option<f64>,
+    /// Processes or devices involved, each listed once.
+    details: Vec<String>,
+}
+
+/// Count the events in messages logged at or after `since` seconds since boot.
+fn summarise(messages: &[(f64, String)], since: f64) -> Vec<Entry> {
+    let mut entries = EVENTS
+        .iter()
+        .map(|event| Entry {
+            event: *event,
+            count: 0,
+            latest: None,
+            details: Vec::new(),
+        })
+        .collect::<Vec<Entry>>();
+
+    for (time, message) in messages.iter().filter(|(time, _)| *time >= since) {
+        if let Some((event, detail)) = classify(message) {
+            let entry = &mut entries[EVENTS.iter().position(|e| *e == event).unwrap()];
+            entry.count += 1;
+            entry.latest = Some(entry.latest.map_or(*time, |latest| latest.max(*time)));
+            if !entry.details.contains(&detail) {
+                entry.details.push(detail);
+            }
+        }
+    }
+    entries
+}
+
+pub fn print_kernel(cfg: &KernelConfig, time_cfg: &TimeFormatConfig, indent: &str) {
+    let header = ["Event", "Count", "Latest", "Details"];
+
+    println!("Kernel:");
+    let messages = match read_kmsg().ok().or_else(read_dmesg) {
+        Some(messages) => messages,
+        None => {
+            println!("{}Unable to read the kernel ring buffer", indent);
+            return;
+        }
+    };
+    let monotonic = monotonic_seconds().unwrap_or(0.0);
+    let since = cfg
+        .window_minutes
+        .map_or(0.0, |minutes| monotonic - minutes as f64 * 60.0);
+    let entries = summarise(&messages, since);
+
+    let now = Utc::now();
+    let formatted_entries = entries
+        .iter()
+        .map(|entry| {
+            [
+                entry.event.label().to_string(),
+                entry.count.to_string(),
+                entry
+                    .latest
+                    .map(|latest| {
+                        let time = now - Duration::seconds((monotonic - latest).max(0.0) as i64);
+                        time_format::format_time(&time, &now, time_cfg)
+                    })
+                    .unwrap_or_else(|| "-".to_string()),
+                entry.details.join(", "),
+            ]
+        })
+        .collect::<Vec<[String; 4]>>();
+
+    let column_widths = util::column_widths(
+        &header,
+        formatted_entries
+            .iter()
+            .map(|cells| cells.iter().map(|cell| cell.chars().count()).collect()),
+    );
+
+    util::print_row(header, &column_widths, Some(indent));
+    entries
+        .iter()
+        .zip(formatted_entries.iter())
+        .for_each(|(entry, cells)| {
+            let formatted_cells =
+                util::format_width(cells.iter().map(|cell| cell.as_str()), &column_widths);
+            let colours = [
+                None,
+                Some(util::select_colour_number(entry.count, &cfg.count)),
+                None,
+                None,
+            ];
+            util::print_row_colour(formatted_cells, colours, Some(indent))
+        });
+}
+
+#[cfg(test)]
+mod test {
+    use crate::components::kernel::{self, Event};
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(
+            kernel::classify(
+                "Out of memory: Killed process 4242 (java) total-vm:8123456kB, anon-rss:6000000kB"
+            ),
+            Some((Event::OomKill, "java".to_string()))
+        );
+        assert_eq!(
+            kernel::classify(
+                "Memory cgroup out of memory: Killed process 17 (node) total-vm:1024kB"
+            ),
+            Some((Event::OomKill, "node".to_string()))
+        );
+        assert_eq!(
+            kernel::classify(
+                "nginx[1834]: segfault at 0 ip 000055d0 sp 00007ffd error 4 in nginx[55d0+1000]"
+            ),
+            Some((Event::Segfault, "nginx".to_string()))
+        );
+        assert_eq!(
+            kernel::classify("INFO: task jbd2/sda1-8:312 blocked for more than 120 seconds."),
+            Some((Event::HungTask, "jbd2/sda1-8".to_string()))
+        );
+        assert_eq!(
+            kernel::classify("blk_update_request: I/O error, dev sdb, sector 2048 op 0x0:(READ)"),
+            Some((Event::IoError, "sdb".to_string()))
+        );
+        assert_eq!(
+            kernel::classify("Buffer I/O error on dev sdb1, logical block 0, async page read"),
+            Some((Event::IoError, "sdb1".to_string()))
+        );
+        assert_eq!(kernel::classify("EXT4-fs (sda1): mounted filesystem"), None);
+    }
+
+    #[test]
+    fn test_parse_records() {
+        assert_eq!(
+            kernel::parse_kmsg_record(
+                "3,1024,51234567,-;Out of memory: Killed process 1 (a)\n SUBSYSTEM=mem\n"
+            ),
+            Some((51.234567, "Out of memory: Killed process 1 (a)".to_string()))
+        );
+        assert_eq!(
+            kernel::parse_dmesg_line("[   51.234567] nginx[1]: segfault at 0"),
+            Some((51.234567, "nginx[1]: segfault at 0".to_string()))
+        );
+        assert_eq!(kernel::parse_dmesg_line("no timestamp"), None);
+    }
+
+    #[test]
+    fn test_summarise() {
+        let messages = [
+            (10.0, "Out of memory: Killed process 1 (java)".to_string()),
+            (500.0, "Out of memory: Killed process 2 (java)".to_string()),
+            (
+                600.0,
+                "Out of memory: Killed process 3 (chrome)".to_string(),
+            ),
+            (700.0, "random message".to_string()),
+        ];
+        let entries = kernel::summarise(&messages, 100.0);
+        assert_eq!(entries[0].event, Event::OomKill);
+        assert_eq!(entries[0].count, 2);
+        assert_eq!(entries[0].latest, Some(600.0));
+        assert_eq!(entries[0].details, vec!["java", "chrome"]);
+        assert!(entries[1..].iter().all(|entry| entry.count == 0));
+    }
+}
@@ -1,5 +1,7 @@
-use crate::config::LastLoginConfig;
+use crate::config::{LastLoginConfig, TimeFormatConfig, TimeStyle};
+use crate::time_format;
 use crate::util;
+use chrono::{DateTime, Utc};
 use colored::Color;
 use lazy_regex::regex;
 use std::process::Command;
@@ -35,7 +37,7 @@ fn user_last_logins_output(
     since: Option<&String>,
 ) -> String {
     let mut command = Command::new("last");
-    command.arg("--ip").arg("--time-format=full");
+    command.arg("--ip").arg("--time-format=iso");
     if let Some(max_lines) = max_lines {
         command.arg("--limit").arg(max_lines.to_string());
     }
@@ -52,6 +54,45 @@ fn user_last_logins_output(
     output
 }
 
+/// Parse an ISO 8601 time as output by `last --time-format=iso`, ignoring any trailing session length e.g. `(00:39)`.
+fn parse_time(time: &str) -> Option<DateTime<Utc>> {
+    let time = time.split_whitespace().next()?;
+    DateTime::parse_from_rfc3339(time)
+        .ok()
+        .map(|time| time.with_timezone(&Utc))
+}
+
+fn format_start_time(entry: &Entry, now: &DateTime<Utc>, time_cfg: &TimeFormatConfig) -> String {
+    match parse_time(&entry.start_time) {
+        Some(start_time) => time_format::format_time(&start_time, now, time_cfg),
+        None => entry.start_time.clone(),
+    }
+}
+
+/// Format the end of a session. Relative style shows the length of the session while absolute style shows the end time.
+/// Sessions without an end time show the reason instead e.g. `still logged in` or `crash`.
+fn format_end_time(entry: &Entry, time_cfg: &TimeFormatConfig) -> String {
+    match (parse_time(&entry.start_time), parse_time(&entry.end_time)) {
+        (Some(start_time), Some(end_time)) => match time_cfg.style {
+            TimeStyle::Relative => time_format::format_duration(
+                end_time
+                    .signed_duration_since(start_time)
+                    .num_seconds()
+                    .max(0) as u64,
+                time_cfg.max_units,
+            ),
+            TimeStyle::Absolute => time_format::format_absolute(&end_time, time_cfg),
+        },
+        _ => entry
+            .end_time
+            .split("  (")
+            .next()
+            .unwrap_or("")
+            .trim()
+            .to_string(),
+    }
+}
+
 fn end_time_colour(end_time: &str) -> Option<Color> {
     match end_time {
         "still logged in" => Some(Color::Green),
@@ -59,50 +100,153 @@ fn end_time_colour(end_time: &str) -> Option<Color> {
     }
 }
 
-pub fn print_last_login(cfg: &LastLoginConfig, indent: &str) {
-    let header = ["Username", "Location", "Start", "End"];
+pub fn print_last_login(cfg: &LastLoginConfig, time_cfg: &TimeFormatConfig, indent: &str) {
+    let header = match time_cfg.style {
+        TimeStyle::Relative => ["Username", "Location", "Start", "Duration"],
+        TimeStyle::Absolute => ["Username", "Location", "Start", "End"],
+    };
 
     let mut entries = Vec::new();
     for (username, user_config) in cfg.users.iter() {
         let output = user_last_logins_output(username, user_config.max_lines, cfg.since.as_ref());
 
-        entries.extend(output.lines().flat_map(|line| parse_entry(line)));
+        entries.extend(output.lines().flat_map(parse_entry));
     }
 
+    let now = Utc::now();
+    let rows = entries
+        .iter()
+        .map(|entry| {
+            (
+                format_start_time(entry, &now, time_cfg),
+                format_end_time(entry, time_cfg),
+            )
+        })
+        .collect::<Vec<(String, String)>>();
+
     let column_widths = util::column_widths(
         &header,
-        entries.iter().map(|entry| {
-            vec![
-                entry.username.len(),
-                entry.location.len(),
-                entry.start_time.len(),
-                entry.end_time.len(),
-            ]
-        }),
+        entries
+            .iter()
+            .zip(rows.iter())
+            .map(|(entry, (start, end))| {
+                vec![
+                    entry.username.len(),
+                    entry.location.len(),
+                    start.len(),
+                    end.len(),
+                ]
+            }),
     );
 
     println!("Logins:");
     util::print_row(header, &column_widths, Some(indent));
-    entries.iter().for_each(|entry| {
-        let formatted_cells = util::format_width(
-            [
-                entry.username.as_str(),
-                entry.location.as_str(),
-                entry.start_time.as_str(),
-                entry.end_time.as_str(),
-            ],
-            &column_widths,
+    entries
+        .iter()
+        .zip(rows.iter())
+        .for_each(|(entry, (start, end))| {
+            let formatted_cells = util::format_width(
+                [
+                    entry.username.as_str(),
+                    entry.location.as_str(),
+                    start.as_str(),
+                    end.as_str(),
+                ],
+                &column_widths,
+            );
+            let user_config = cfg
+                .users
+                .get(&entry.username)
+                .expect("Could not find config for user");
+            let colours = [
+                user_config.username_colour,
+                None,
+                None,
+                end_time_colour(end),
+            ];
+            util::print_row_colour(formatted_cells, colours, Some(indent))
+        });
+}
+
+#[cfg(test)]
+mod test {
+    use crate::components::last_login::{self, Entry};
+    use crate::config::{TimeFormatConfig, TimeStyle};
+    use chrono::{TimeZone, Utc};
+
+    /// Output of `last --ip --time-format=iso alice` for a finished session, a session ended by a crash
+    /// and a session which is still logged in.
+    const OUTPUT: &str = "alice    pts/1        10.0.0.7         2024-03-04T12:01:42+00:00   still logged in
+alice    pts/2        10.0.0.5         2024-03-04T10:38:22+00:00 - crash                      (01:06)
+alice    pts/0        192.168.1.10     2024-03-04T09:15:02+00:00 - 2024-03-04T09:54:21+00:00  (00:39)
+
+wtmp begins 2024-03-04T08:15:02+00:00
+";
+
+    fn entries() -> Vec<Entry> {
+        OUTPUT.lines().flat_map(last_login::parse_entry).collect()
+    }
+
+    fn format(entries: &[Entry], time_cfg: &TimeFormatConfig) -> Vec<[String; 2]> {
+        let now = Utc.with_ymd_and_hms(2024, 3, 4, 14, 0, 0).unwrap();
+        entries
+            .iter()
+            .map(|entry| {
+                [
+                    last_login::format_start_time(entry, &now, time_cfg),
+                    last_login::format_end_time(entry, time_cfg),
+                ]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_entry() {
+        let entries = entries();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].username, "alice");
+        assert_eq!(entries[0].location, "10.0.0.7");
+        assert_eq!(entries[0].start_time, "2024-03-04T12:01:42+00:00");
+        assert_eq!(entries[0].end_time, "still logged in");
+        assert_eq!(entries[1].start_time, "2024-03-04T10:38:22+00:00");
+        assert_eq!(entries[1].end_time, "crash");
+        assert_eq!(entries[2].location, "192.168.1.10");
+        assert_eq!(entries[2].start_time, "2024-03-04T09:15:02+00:00");
+        assert_eq!(entries[2].end_time, "2024-03-04T09:54:21+00:00  (00:39)");
+    }
+
+    #[test]
+    fn test_format_relative() {
+        assert_eq!(
+            format(&entries(), &TimeFormatConfig::default()),
+            vec![
+                ["1h 58m ago".to_string(), "still logged in".to_string()],
+                ["3h 21m ago".to_string(), "crash".to_string()],
+                ["4h 44m ago".to_string(), "39m 19s".to_string()],
+            ]
         );
-        let user_config = cfg
-            .users
-            .get(&entry.username)
-            .expect("Could not find config for user");
-        let colours = [
-            user_config.username_colour,
-            None,
-            None,
-            end_time_colour(&entry.end_time),
-        ];
-        util::print_row_colour(formatted_cells, colours, Some(indent))
-    });
+    }
+
+    #[test]
+    fn test_format_absolute() {
+        let time_cfg = TimeFormatConfig {
+            style: TimeStyle::Absolute,
+            timezone: Some(chrono_tz::Europe::Berlin),
+            ..TimeFormatConfig::default()
+        };
+        assert_eq!(
+            format(&entries(), &time_cfg),
+            vec![
+                [
+                    "2024-03-04 13:01".to_string(),
+                    "still logged in".to_string()
+                ],
+                ["2024-03-04 11:38".to_string(), "crash".to_string()],
+                [
+                    "2024-03-04 10:15".to_string(),
+                    "2024-03-04 10:54".to_string()
+                ],
+            ]
+        );
+    }
 }
@@ -0,0 +1,234 @@
+use crate::config::ListeningConfig;
+use crate::util;
+use colored::Color;
+use std::collections::HashMap;
+use std::fs;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+/// Files listing sockets with the state of sockets accepting connections or datagrams.
+/// TCP sockets are listening in state `0A` (`TCP_LISTEN`) while UDP sockets which are not connected are in state `07` (`TCP_CLOSE`).
+const PROC_NET_FILES: [(&str, &str, &str); 4] = [
+    ("tcp", "/proc/net/tcp", "0A"),
+    ("tcp6", "/proc/net/tcp6", "0A"),
+    ("udp", "/proc/net/udp", "07"),
+    ("udp6", "/proc/net/udp6", "07"),
+];
+
+#[derive(Debug, PartialEq)]
+struct Socket {
+    protocol: &'static str,
+    address: IpAddr,
+    port: u16,
+    inode: u64,
+}
+
+/// Parse an address from `/proc/net`, which is written as hex words in host byte order e.g. `0100007F` for 127.0.0.1.
+fn parse_address(hex: &str) -> Option<IpAddr> {
+    let words = (0..hex.len() / 8)
+        .map(|i| u32::from_str_radix(hex.get(i * 8..i * 8 + 8)?, 16).ok())
+        .collect::<Option<Vec<u32>>>()?;
+    let bytes = words
+        .iter()
+        .flat_map(|word| word.to_ne_bytes())
+        .collect::<Vec<u8>>();
+    match bytes.len() {
+        4 => Some(IpAddr::V4(Ipv4Addr::new(
+            bytes[0], bytes[1], bytes[2], bytes[3],
+        ))),
+        16 => {
+            let mut octets = [0; 16];
+            octets.copy_from_slice(&bytes);
+            Some(IpAddr::V6(Ipv6Addr::from(octets)))
+        }
+        _ => None,
+    }
+}
+
+/// Parse the sockets in the given state from one of the `/proc/net/{tcp,udp}{,6}` files.
+fn parse_proc_net(contents: &str, protocol: &'static str, state: &str) -> Vec<Socket> {
+    contents
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields = line.split_whitespace().collect::<Vec<&str>>();
+            if fields.get(3) != Some(&state) {
+                return None;
+            }
+            let (address, port) = fields.get(1)?.split_once(':')?;
+            Some(Socket {
+                protocol,
+                address: parse_address(address)?,
+                port: u16::from_str_radix(port, 16).ok()?,
+                inode: fields.get(9)?.parse().ok()?,
+            })
+        })
+        .collect()
+}
+
+/// Parse the inode from the target of a file descriptor link to a socket e.g. `socket:[12345]`.
+fn parse_socket_inode(link: &str) -> Option<u64> {
+    link.strip_prefix("socket:[")?
+        .strip_suffix(']')?
+        .parse()
+        .ok()
+}
+
+/// Map socket inodes to the name and pid of the process with a file descriptor for the socket.
+/// Only processes this user may inspect are found, which is every process when run as root.
+fn socket_owners() -> HashMap<u64, String> {
+    let mut owners = HashMap::new();
+    let proc_dir = match fs::read_dir("/proc") {
+        Ok(dir) => dir,
+        Err(_) => return owners,
+    };
+    for process in proc_dir.filter_map(|entry| entry.ok()) {
+        let pid = match process.file_name().to_str().map(|pid| pid.parse::<u32>()) {
+            Some(Ok(pid)) => pid,
+            _ => continue,
+        };
+        let fds = match fs::read_dir(process.path().join("fd")) {
+            Ok(fds) => fds,
+            Err(_) => continue,
+        };
+        let name = fs::read_to_string(process.path().join("comm"))
+            .map(|comm| comm.trim().to_string())
+            .unwrap_or_default();
+        for fd in fds.filter_map(|fd| fd.ok()) {
+            let inode = fs::read_link(fd.path())
+                .ok()
+                .and_then(|link| parse_socket_inode(&link.to_string_lossy()));
+            if let Some(inode) = inode {
+                owners
+                    .entry(inode)
+                    .or_insert_with(|| format!("{} ({})", name, pid));
+            }
+        }
+    }
+    owners
+}
+
+fn read_sockets(cfg: &ListeningConfig) -> Vec<Socket> {
+    let mut sockets = PROC_NET_FILES
+        .iter()
+        .flat_map(|(protocol, path, state)| {
+            fs::read_to_string(path)
+                .map(|contents| parse_proc_net(&contents, protocol, state))
+                .unwrap_or_default()
+        })
+        .filter(|socket| !(cfg.hide_loopback && socket.address.is_loopback()))
+        .collect::<Vec<Socket>>();
+    sockets.sort_by_key(|socket| (socket.port, socket.protocol));
+    sockets
+}
+
+pub fn print_listening(cfg: &ListeningConfig, indent: &str) {
+    let header = ["Proto", "Address", "Port", "Process"];
+
+    let sockets = read_sockets(cfg);
+    println!("Listening:");
+    if sockets.is_empty() {
+        println!("{}No listening sockets found", indent);
+        return;
+    }
+
+    let owners = socket_owners();
+    let formatted_entries = sockets
+        .iter()
+        .map(|socket| {
+            [
+                socket.protocol.to_string(),
+                socket.address.to_string(),
+                socket.port.to_string(),
+                owners
+                    .get(&socket.inode)
+                    .cloned()
+                    .unwrap_or_else(|| "-".to_string()),
+            ]
+        })
+        .collect::<Vec<[String; 4]>>();
+
+    let column_widths = util::column_widths(
+        &header,
+        formatted_entries
+            .iter()
+            .map(|cells| cells.iter().map(|cell| cell.chars().count()).collect()),
+    );
+
+    util::print_row(header, &column_widths, Some(indent));
+    sockets
+        .iter()
+        .zip(formatted_entries.iter())
+        .for_each(|(socket, cells)| {
+            let formatted_cells =
+                util::format_width(cells.iter().map(|cell| cell.as_str()), &column_widths);
+            let port_colour = match cfg.allowed_ports.is_empty() {
+                true => None,
+                false if cfg.allowed_ports.contains(&socket.port) => Some(Color::Green),
+                false => Some(Color::Red),
+            };
+            util::print_row_colour(
+                formatted_cells,
+                [None, None, port_colour, None],
+                Some(indent),
+            )
+        });
+}
+
+#[cfg(test)]
+mod test {
+    use crate::components::listening::{self, Socket};
+    use std::net::IpAddr;
+
+    #[test]
+    fn test_parse_proc_net() {
+        let tcp = "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 0100007F:0277 00000000:0000 0A 00000000:00000000 00:00000000 00000000     0        0 21746 1 0000000000000000 100 0 0 10 0
+   1: 00000000:0016 00000000:0000 0A 00000000:00000000 00:00000000 00000000     0        0 19043 1 0000000000000000 100 0 0 10 0
+   2: 0F02000A:0016 0102000A:C7A2 01 00000000:00000000 02:0009B36A 00000000     0        0 84711 4 0000000000000000 20 4 31 10 -1
+";
+        assert_eq!(
+            listening::parse_proc_net(tcp, "tcp", "0A"),
+            vec![
+                Socket {
+                    protocol: "tcp",
+                    address: "127.0.0.1".parse::<IpAddr>().unwrap(),
+                    port: 631,
+                    inode: 21746,
+                },
+                Socket {
+                    protocol: "tcp",
+                    address: "0.0.0.0".parse::<IpAddr>().unwrap(),
+                    port: 22,
+                    inode: 19043,
+                },
+            ]
+        );
+
+        let udp6 = "  sl  local_address                         remote_address                        st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode ref pointer drops
+  310: 00000000000000000000000001000000:0035 00000000000000000000000000000000:0000 07 00000000:00000000 00:00000000 00000000   101        0 17855 2 0000000000000000 0
+";
+        let sockets = listening::parse_proc_net(udp6, "udp6", "07");
+        assert_eq!(sockets[0].address, "::1".parse::<IpAddr>().unwrap());
+        assert_eq!(sockets[0].port, 53);
+    }
+
+    #[test]
+    fn test_parse_address() {
+        assert_eq!(
+            listening::parse_address("0F02000A"),
+            Some("10.0.2.15".parse().unwrap())
+        );
+        assert_eq!(
+            listening::parse_address("B80D01200000000067452301EFCDAB89"),
+            Some("2001:db8::123:4567:89ab:cdef".parse().unwrap())
+        );
+        assert_eq!(listening::parse_address("XYZ"), None);
+    }
+
+    #[test]
+    fn test_parse_socket_inode() {
+        assert_eq!(listening::parse_socket_inode("socket:[21746]"), Some(21746));
+        assert_eq!(listening::parse_socket_inode("pipe:[21746]"), None);
+        assert_eq!(listening::parse_socket_inode("/dev/null"), None);
+    }
+}
@@ -1,5 +1,19 @@
+pub mod custom;
+pub mod disk_health;
+pub mod disk_io;
 pub mod disks;
+pub mod files;
 pub mod general_info;
+pub mod journal;
+pub mod kernel;
 pub mod last_login;
+pub mod listening;
+pub mod pi_health;
+pub mod power;
+pub mod pressure;
+pub mod raid;
+pub mod reachability;
 pub mod services;
+pub mod storage_history;
 pub mod temperature;
+pub mod ups;
@@ -0,0 +1,140 @@
+use crate::config::{PiHealthConfig, UnitsConfig};
+use crate::units;
+use colored::*;
+use std::fs;
+use std::process::Command;
+
+const THROTTLED_PATH: &str = "/sys/devices/platform/soc/soc:firmware/get_throttled";
+
+/// Conditions reported by the firmware with the bit set while the condition is active
+/// and the bit set if the condition has occurred since boot.
+const CONDITIONS: [(&str, u32, u32); 4] = [
+    ("Under-voltage", 0, 16),
+    ("Freq capped", 1, 17),
+    ("Throttled", 2, 18),
+    ("Soft temp limit", 3, 19),
+];
+
+const ALIGN_LENGTH: usize = 15;
+
+#[derive(Debug, PartialEq)]
+enum Condition {
+    Active,
+    OccurredSinceBoot,
+    Never,
+}
+
+fn condition(throttled: u32, active_bit: u32, occurred_bit: u32) -> Condition {
+    if throttled & (1 << active_bit) != 0 {
+        Condition::Active
+    } else if throttled & (1 << occurred_bit) != 0 {
+        Condition::OccurredSinceBoot
+    } else {
+        Condition::Never
+    }
+}
+
+/// Parse the throttled bitmask from either `vcgencmd get_throttled` e.g. `throttled=0x50005`
+/// or the firmware sysfs file, which contains the bitmask in hex without a prefix.
+fn parse_throttled(output: &str) -> Option<u32> {
+    let value = output.trim();
+    let value = value.strip_prefix("throttled=").unwrap_or(value);
+    let value = value.strip_prefix("0x").unwrap_or(value);
+    u32::from_str_radix(value, 16).ok()
+}
+
+fn vcgencmd(args: &[&str]) -> Option<String> {
+    let output = Command::new("vcgencmd").args(args).output().ok()?;
+    match output.status.success() {
+        true => Some(String::from_utf8_lossy(&output.stdout).to_string()),
+        false => None,
+    }
+}
+
+fn throttled() -> Option<u32> {
+    fs::read_to_string(THROTTLED_PATH)
+        .ok()
+        .and_then(|contents| parse_throttled(&contents))
+        .or_else(|| parse_throttled(&vcgencmd(&["get_throttled"])?))
+}
+
+/// Parse the output of `vcgencmd get_mem <arm|gpu>` e.g. `gpu=76M` into the size in MiB.
+fn parse_mem(output: &str) -> Option<u64> {
+    let (_, size) = output.trim().split_once('=')?;
+    size.strip_suffix('M')?.parse().ok()
+}
+
+fn memory_split(units_cfg: &UnitsConfig) -> String {
+    let mem = |part| vcgencmd(&["get_mem", part]).and_then(|output| parse_mem(&output));
+    match (mem("arm"), mem("gpu")) {
+        (Some(arm), Some(gpu)) => format!(
+            "{} ARM, {} GPU",
+            units::format_bytes(arm * 1024 * 1024, &units_cfg.bytes),
+            units::format_bytes(gpu * 1024 * 1024, &units_cfg.bytes)
+        ),
+        _ => "N/A".to_string(),
+    }
+}
+
+fn format_condition(condition: &Condition) -> ColoredString {
+    match condition {
+        Condition::Active => "Now".red(),
+        Condition::OccurredSinceBoot => "Since boot".yellow(),
+        Condition::Never => "OK".green(),
+    }
+}
+
+fn row(label: &str, value: impl std::fmt::Display, indent: &str) {
+    println!("{}{:.<max$}: {}", indent, label, value, max = ALIGN_LENGTH);
+}
+
+pub fn print_pi_health(cfg: &PiHealthConfig, units_cfg: &UnitsConfig, indent: &str) {
+    println!("Pi health:");
+    match throttled() {
+        Some(throttled) => {
+            for (label, active_bit, occurred_bit) in CONDITIONS {
+                let condition = condition(throttled, active_bit, occurred_bit);
+                row(label, format_condition(&condition), indent);
+            }
+        }
+        None => println!("{}Throttling status unavailable", indent),
+    }
+    if cfg.memory_split {
+        row("Memory split", memory_split(units_cfg), indent);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::components::pi_health::{self, Condition};
+
+    #[test]
+    fn test_parse_throttled() {
+        assert_eq!(
+            pi_health::parse_throttled("throttled=0x50005\n"),
+            Some(0x50005)
+        );
+        assert_eq!(pi_health::parse_throttled("0\n"), Some(0));
+        assert_eq!(pi_health::parse_throttled("80008"), Some(0x80008));
+        assert_eq!(pi_health::parse_throttled(""), None);
+    }
+
+    #[test]
+    fn test_condition() {
+        let throttled = 0x50005;
+        assert_eq!(pi_health::condition(throttled, 0, 16), Condition::Active);
+        assert_eq!(pi_health::condition(throttled, 1, 17), Condition::Never);
+        assert_eq!(pi_health::condition(throttled, 2, 18), Condition::Active);
+        assert_eq!(
+            pi_health::condition(0x80000, 3, 19),
+            Condition::OccurredSinceBoot
+        );
+    }
+
+    #[test]
+    fn test_parse_mem() {
+        assert_eq!(pi_health::parse_mem("gpu=76M\n"), Some(76));
+        assert_eq!(pi_health::parse_mem("arm=948M"), Some(948));
+        assert_eq!(pi_health::parse_mem("error=1"), None);
+    }
+}
@@ -0,0 +1,259 @@
+use crate::config::{PowerConfig, TimeFormatConfig};
+use crate::time_format;
+use crate::util;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+const POWER_SUPPLY_PATH: &str = "/sys/class/power_supply";
+
+#[derive(Debug, PartialEq)]
+struct Entry {
+    name: String,
+    kind: String,
+    status: String,
+    /// Charge of a battery as a percentage.
+    charge: Option<u64>,
+    /// Full capacity of a battery as a percentage of its design capacity.
+    health: Option<u64>,
+    /// Seconds until a battery is empty when discharging or full when charging.
+    time_remaining: Option<u64>,
+}
+
+/// Parse the `uevent` file of a power supply into a map from property name, without the `POWER_SUPPLY_` prefix, to value.
+fn parse_uevent(contents: &str) -> HashMap<&str, &str> {
+    contents
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once('=')?;
+            Some((key.strip_prefix("POWER_SUPPLY_").unwrap_or(key), value))
+        })
+        .collect()
+}
+
+fn number(props: &HashMap<&str, &str>, key: &str) -> Option<u64> {
+    // Some drivers report the current as negative while discharging.
+    props
+        .get(key)?
+        .parse::<i64>()
+        .ok()
+        .map(|value| value.unsigned_abs())
+}
+
+/// Batteries report either energy in µWh and power in µW or charge in µAh and current in µA.
+/// Returns the current, full and design capacity along with the rate of the first set of properties present.
+fn capacity(props: &HashMap<&str, &str>) -> Option<(u64, u64, Option<u64>, Option<u64>)> {
+    [("ENERGY", "POWER"), ("CHARGE", "CURRENT")]
+        .iter()
+        .find_map(|(capacity, rate)| {
+            Some((
+                number(props, &format!("{}_NOW", capacity))?,
+                number(props, &format!("{}_FULL", capacity))?,
+                number(props, &format!("{}_FULL_DESIGN", capacity)),
+                number(props, &format!("{}_NOW", rate)),
+            ))
+        })
+}
+
+fn battery_entry(name: &str, props: &HashMap<&str, &str>) -> Entry {
+    let status = props.get("STATUS").copied().unwrap_or("Unknown");
+    let capacity = capacity(props);
+
+    let charge = number(props, "CAPACITY").or_else(|| {
+        let (now, full, _, _) = capacity?;
+        (full > 0).then(|| (100 * now / full).min(100))
+    });
+    let health = capacity.and_then(|(_, full, design, _)| match design {
+        Some(design) if design > 0 => Some(100 * full / design),
+        _ => None,
+    });
+    let time_remaining = capacity.and_then(|(now, full, _, rate)| {
+        let remaining = match status {
+            "Discharging" => now,
+            "Charging" => full.saturating_sub(now),
+            _ => return None,
+        };
+        match rate {
+            Some(rate) if rate > 0 => Some(remaining * 60 * 60 / rate),
+            _ => None,
+        }
+    });
+
+    Entry {
+        name: name.to_string(),
+        kind: props.get("TYPE").copied().unwrap_or("Battery").to_string(),
+        status: status.to_string(),
+        charge,
+        health,
+        time_remaining,
+    }
+}
+
+/// Create the entry for a power supply from the properties in its `uevent` file.
+/// Supplies which are not batteries e.g. `Mains` or `USB` only report whether they are online.
+fn entry(name: &str, props: &HashMap<&str, &str>) -> Entry {
+    match props.get("TYPE").copied() {
+        Some("Battery") | Some("UPS") => battery_entry(name, props),
+        None if props.contains_key("STATUS") => battery_entry(name, props),
+        kind => Entry {
+            name: name.to_string(),
+            kind: kind.unwrap_or("Unknown").to_string(),
+            status: match props.get("ONLINE").copied() {
+                Some("1") => "Online".to_string(),
+                Some(_) => "Offline".to_string(),
+                None => "Unknown".to_string(),
+            },
+            charge: None,
+            health: None,
+            time_remaining: None,
+        },
+    }
+}
+
+fn read_entries() -> Vec<Entry> {
+    let mut names = match fs::read_dir(POWER_SUPPLY_PATH) {
+        Ok(dir) => dir
+            .filter_map(|supply| supply.ok()?.file_name().into_string().ok())
+            .collect::<Vec<String>>(),
+        Err(_) => return Vec::new(),
+    };
+    names.sort();
+    names
+        .iter()
+        .filter_map(|name| {
+            let uevent = fs::read_to_string(Path::new(POWER_SUPPLY_PATH).join(name).join("uevent"));
+            Some(entry(name, &parse_uevent(&uevent.ok()?)))
+        })
+        .collect()
+}
+
+fn format_percent(value: Option<u64>) -> String {
+    value
+        .map(|value| value.to_string())
+        .unwrap_or_else(|| "-".to_string())
+}
+
+pub fn print_power(cfg: &PowerConfig, time_cfg: &TimeFormatConfig, indent: &str) {
+    let header = [
+        "Supply",
+        "Type",
+        "Status",
+        "Charge(%)",
+        "Health(%)",
+        "Time Left",
+    ];
+
+    let entries = read_entries();
+    println!("Power:");
+    if entries.is_empty() {
+        println!("{}No power supplies found", indent);
+        return;
+    }
+
+    let formatted_entries = entries
+        .iter()
+        .map(|entry| {
+            [
+                entry.name.clone(),
+                entry.kind.clone(),
+                entry.status.clone(),
+                format_percent(entry.charge),
+                format_percent(entry.health),
+                entry
+                    .time_remaining
+                    .map(|seconds| time_format::format_duration(seconds, time_cfg.max_units))
+                    .unwrap_or_else(|| "-".to_string()),
+            ]
+        })
+        .collect::<Vec<[String; 6]>>();
+
+    let column_widths = util::column_widths(
+        &header,
+        formatted_entries
+            .iter()
+            .map(|cells| cells.iter().map(|cell| cell.len()).collect()),
+    );
+
+    util::print_row(header, &column_widths, Some(indent));
+    entries
+        .iter()
+        .zip(formatted_entries.iter())
+        .for_each(|(entry, cells)| {
+            let formatted_cells =
+                util::format_width(cells.iter().map(|cell| cell.as_str()), &column_widths);
+            let colours = [
+                None,
+                None,
+                None,
+                entry
+                    .charge
+                    .map(|charge| util::select_colour_below(charge, &cfg.charge)),
+                entry
+                    .health
+                    .map(|health| util::select_colour_below(health, &cfg.health)),
+                None,
+            ];
+            util::print_row_colour(formatted_cells, colours, Some(indent))
+        });
+}
+
+#[cfg(test)]
+mod test {
+    use crate::components::power::{self, Entry};
+
+    #[test]
+    fn test_energy_battery() {
+        let uevent = "POWER_SUPPLY_NAME=BAT0
+POWER_SUPPLY_TYPE=Battery
+POWER_SUPPLY_STATUS=Discharging
+POWER_SUPPLY_PRESENT=1
+POWER_SUPPLY_POWER_NOW=9500000
+POWER_SUPPLY_ENERGY_FULL_DESIGN=57000000
+POWER_SUPPLY_ENERGY_FULL=45600000
+POWER_SUPPLY_ENERGY_NOW=19000000
+POWER_SUPPLY_CAPACITY=41
+";
+        assert_eq!(
+            power::entry("BAT0", &power::parse_uevent(uevent)),
+            Entry {
+                name: "BAT0".to_string(),
+                kind: "Battery".to_string(),
+                status: "Discharging".to_string(),
+                charge: Some(41),
+                health: Some(80),
+                time_remaining: Some(2 * 60 * 60),
+            }
+        );
+    }
+
+    #[test]
+    fn test_charge_battery() {
+        let uevent = "POWER_SUPPLY_TYPE=Battery
+POWER_SUPPLY_STATUS=Charging
+POWER_SUPPLY_CURRENT_NOW=-1000000
+POWER_SUPPLY_CHARGE_FULL=3000000
+POWER_SUPPLY_CHARGE_NOW=1500000
+";
+        let entry = power::entry("ups", &power::parse_uevent(uevent));
+        assert_eq!(entry.charge, Some(50));
+        assert_eq!(entry.health, None);
+        assert_eq!(entry.time_remaining, Some(90 * 60));
+
+        let full = uevent.replace("=Charging", "=Full");
+        let entry = power::entry("ups", &power::parse_uevent(&full));
+        assert_eq!(entry.time_remaining, None);
+    }
+
+    #[test]
+    fn test_mains() {
+        let entry = power::entry(
+            "AC",
+            &power::parse_uevent(
+                "POWER_SUPPLY_NAME=AC\nPOWER_SUPPLY_TYPE=Mains\nPOWER_SUPPLY_ONLINE=0\n",
+            ),
+        );
+        assert_eq!(entry.kind, "Mains");
+        assert_eq!(entry.status, "Offline");
+        assert_eq!(entry.charge, None);
+    }
+}
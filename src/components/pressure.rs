@@ -0,0 +1,132 @@
+use crate::config::{ConditionalColour, PressureConfig};
+use crate::util;
+use std::fs;
+
+const RESOURCES: [&str; 3] = ["cpu", "memory", "io"];
+
+#[derive(Debug, PartialEq)]
+struct Entry {
+    kind: String,
+    avg10: f32,
+    avg60: f32,
+    avg300: f32,
+}
+
+/// Parse a single line of a `/proc/pressure/*` file e.g. `some avg10=0.00 avg60=0.00 avg300=0.00 total=0`.
+fn parse_entry(line: &str) -> Option<Entry> {
+    let mut items = line.split_whitespace();
+    let kind = items.next()?.to_string();
+
+    let mut avg10 = None;
+    let mut avg60 = None;
+    let mut avg300 = None;
+    for item in items {
+        let (key, value) = item.split_once('=')?;
+        match key {
+            "avg10" => avg10 = value.parse::<f32>().ok(),
+            "avg60" => avg60 = value.parse::<f32>().ok(),
+            "avg300" => avg300 = value.parse::<f32>().ok(),
+            _ => {}
+        }
+    }
+
+    Some(Entry {
+        kind,
+        avg10: avg10?,
+        avg60: avg60?,
+        avg300: avg300?,
+    })
+}
+
+fn colour_for<'a>(kind: &str, cfg: &'a PressureConfig) -> &'a ConditionalColour<f32> {
+    match kind {
+        "full" => &cfg.full,
+        _ => &cfg.some,
+    }
+}
+
+pub fn print_pressure(cfg: &PressureConfig, indent: &str) {
+    let header = ["Resource", "Type", "avg10", "avg60", "avg300"];
+
+    let entries = RESOURCES
+        .iter()
+        .flat_map(|resource| {
+            let contents =
+                fs::read_to_string(format!("/proc/pressure/{}", resource)).unwrap_or_default();
+            contents
+                .lines()
+                .flat_map(parse_entry)
+                .map(|entry| (*resource, entry))
+                .collect::<Vec<(&str, Entry)>>()
+        })
+        .collect::<Vec<(&str, Entry)>>();
+
+    println!("Pressure:");
+    if entries.is_empty() {
+        println!("{}Pressure stall information is unavailable", indent);
+        return;
+    }
+
+    let formatted_entries = entries
+        .iter()
+        .map(|(resource, entry)| {
+            [
+                resource.to_string(),
+                entry.kind.clone(),
+                format!("{:.2}", entry.avg10),
+                format!("{:.2}", entry.avg60),
+                format!("{:.2}", entry.avg300),
+            ]
+        })
+        .collect::<Vec<[String; 5]>>();
+
+    let column_widths = util::column_widths(
+        &header,
+        formatted_entries
+            .iter()
+            .map(|cells| cells.iter().map(|cell| cell.len()).collect()),
+    );
+
+    util::print_row(header, &column_widths, Some(indent));
+    entries
+        .iter()
+        .zip(formatted_entries.iter())
+        .for_each(|((_, entry), cells)| {
+            let formatted_cells =
+                util::format_width(cells.iter().map(|cell| cell.as_str()), &column_widths);
+            let cond_colour = colour_for(&entry.kind, cfg);
+            let colours = [
+                None,
+                None,
+                Some(util::select_colour_number(entry.avg10, cond_colour)),
+                Some(util::select_colour_number(entry.avg60, cond_colour)),
+                Some(util::select_colour_number(entry.avg300, cond_colour)),
+            ];
+            util::print_row_colour(formatted_cells, colours, Some(indent))
+        });
+}
+
+#[cfg(test)]
+mod test {
+    use crate::components::pressure::{self, Entry};
+
+    #[test]
+    fn test_parse_entry() {
+        assert_eq!(
+            pressure::parse_entry("some avg10=4.24 avg60=3.79 avg300=3.29 total=27439443"),
+            Some(Entry {
+                kind: "some".to_string(),
+                avg10: 4.24,
+                avg60: 3.79,
+                avg300: 3.29,
+            })
+        );
+        assert_eq!(
+            pressure::parse_entry("full avg10=0.00 avg60=0.00 avg300=0.00 total=0")
+                .map(|entry| entry.kind),
+            Some("full".to_string())
+        );
+        assert_eq!(pressure::parse_entry("some avg10=0.00 total=0"), None);
+        assert_eq!(pressure::parse_entry(""), None);
+    }
+}
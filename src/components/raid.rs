@@ -0,0 +1,305 @@
+use crate::command;
+use crate::config::RaidConfig;
+use crate::util;
+use colored::Color;
+use lazy_regex::regex;
+use std::fs;
+use std::process::Command;
+use std::time::Duration;
+
+#[derive(Debug, PartialEq)]
+struct Entry {
+    name: String,
+    kind: String,
+    state: String,
+    detail: String,
+    progress: String,
+    degraded: bool,
+}
+
+/// Parse the contents of `/proc/mdstat` into an entry for each array.
+fn parse_mdstat(contents: &str) -> Vec<Entry> {
+    let array_regex = regex!(
+        r"^(md\S*) : (\w+)(?: \([\w-]+\))?(?: ((?:raid|linear|multipath|faulty)\w*))? ?(.*)$"
+    );
+    let status_regex = regex!(r"\[(\d+)/(\d+)\] \[([U_]+)\]");
+    let progress_regex = regex!(r"(recovery|resync|reshape|check|repair)\s*=\s*([\d.]+%)");
+    let finish_regex = regex!(r"finish=(\S+)");
+
+    let mut entries: Vec<Entry> = Vec::new();
+    for line in contents.lines() {
+        if let Some(captures) = array_regex.captures(line) {
+            let state = captures[2].to_string();
+            let members = captures.get(4).map_or("", |m| m.as_str());
+            let failed_members = members.matches("(F)").count();
+            entries.push(Entry {
+                name: captures[1].to_string(),
+                kind: captures.get(3).map_or("md", |m| m.as_str()).to_string(),
+                degraded: state != "active" || failed_members > 0,
+                state,
+                detail: match failed_members {
+                    0 => String::new(),
+                    count => format!("{} failed", count),
+                },
+                progress: String::new(),
+            });
+            continue;
+        }
+
+        let entry = match entries.last_mut() {
+            Some(entry) => entry,
+            None => continue,
+        };
+        if let Some(captures) = status_regex.captures(line) {
+            let total = captures[1].parse::<u32>().unwrap_or(0);
+            let active = captures[2].parse::<u32>().unwrap_or(0);
+            if active < total {
+                entry.degraded = true;
+                entry.state = "degraded".to_string();
+            }
+            let status = format!("{}/{} [{}]", active, total, &captures[3]);
+            entry.detail = match entry.detail.is_empty() {
+                true => status,
+                false => format!("{}, {}", status, entry.detail),
+            };
+        } else if let Some(captures) = progress_regex.captures(line) {
+            entry.progress = match finish_regex.captures(line) {
+                Some(finish) => {
+                    format!("{} {} (finish {})", &captures[1], &captures[2], &finish[1])
+                }
+                None => format!("{} {}", &captures[1], &captures[2]),
+            };
+        }
+    }
+    entries
+}
+
+/// Parse the output of `zpool list -H -o name,health,capacity`.
+fn parse_zpool_list(output: &str) -> Vec<Entry> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut items = line.split('\t');
+            let name = items.next()?;
+            let health = items.next()?;
+            let capacity = items.next().unwrap_or("");
+            Some(Entry {
+                name: name.to_string(),
+                kind: "zfs".to_string(),
+                state: health.to_string(),
+                detail: format!("{} used", capacity),
+                progress: String::new(),
+                degraded: health != "ONLINE",
+            })
+        })
+        .collect()
+}
+
+/// Parse the output of `btrfs device stats <mount>`, which lists error counters such as
+/// `[/dev/sda1].write_io_errs    0` for each device in the filesystem.
+fn parse_btrfs_device_stats(mount_point: &str, output: &str) -> Entry {
+    let errors = output
+        .lines()
+        .filter_map(|line| {
+            let (counter, count) = line.rsplit_once(char::is_whitespace)?;
+            let count = count.parse::<u64>().ok()?;
+            match count {
+                0 => None,
+                _ => Some(format!("{} {}", counter.trim(), count)),
+            }
+        })
+        .collect::<Vec<String>>();
+
+    Entry {
+        name: mount_point.to_string(),
+        kind: "btrfs".to_string(),
+        state: match errors.is_empty() {
+            true => "OK".to_string(),
+            false => "errors".to_string(),
+        },
+        detail: errors.join(", "),
+        progress: String::new(),
+        degraded: !errors.is_empty(),
+    }
+}
+
+/// Run a command, killing it if it has not finished within `timeout_ms` e.g. `zpool` waiting on a suspended pool.
+fn command_output(command: &mut Command, timeout_ms: u64) -> Result<String, String> {
+    let program = command.get_program().to_string_lossy().to_string();
+    match command::output_with_timeout(command, Duration::from_millis(timeout_ms)) {
+        Ok(Some(raw_output)) if raw_output.status.success() => {
+            Ok(String::from_utf8_lossy(&raw_output.stdout).to_string())
+        }
+        Ok(Some(raw_output)) => Err(format!("{} failed with {}", program, raw_output.status)),
+        Ok(None) => Err(format!("{} timed out after {} ms", program, timeout_ms)),
+        Err(e) => Err(format!("Failed to run {}: {}", program, e)),
+    }
+}
+
+fn error_entry(name: &str, kind: &str, detail: String) -> Entry {
+    Entry {
+        name: name.to_string(),
+        kind: kind.to_string(),
+        state: "N/A".to_string(),
+        detail,
+        progress: String::new(),
+        degraded: true,
+    }
+}
+
+fn zpool_entries(timeout_ms: u64) -> Vec<Entry> {
+    let mut command = Command::new("zpool");
+    command
+        .arg("list")
+        .arg("-H")
+        .arg("-o")
+        .arg("name,health,capacity");
+    match command_output(&mut command, timeout_ms) {
+        Ok(output) => parse_zpool_list(&output),
+        Err(e) => vec![error_entry("zpool", "zfs", e)],
+    }
+}
+
+fn btrfs_entry(mount_point: &str, timeout_ms: u64) -> Entry {
+    let mut command = Command::new("btrfs");
+    command.arg("device").arg("stats").arg(mount_point);
+    match command_output(&mut command, timeout_ms) {
+        Ok(output) => parse_btrfs_device_stats(mount_point, &output),
+        Err(e) => error_entry(mount_point, "btrfs", e),
+    }
+}
+
+pub fn print_raid(cfg: &RaidConfig, indent: &str) {
+    let header = ["Array", "Type", "State", "Detail", "Progress"];
+
+    // `/proc/mdstat` only exists when the md driver is loaded.
+    let mut entries = parse_mdstat(&fs::read_to_string("/proc/mdstat").unwrap_or_default());
+    if cfg.zfs {
+        entries.extend(zpool_entries(cfg.timeout_ms));
+    }
+    entries.extend(
+        cfg.btrfs_mounts
+            .iter()
+            .map(|mount_point| btrfs_entry(mount_point, cfg.timeout_ms)),
+    );
+
+    println!("Arrays:");
+    if entries.is_empty() {
+        println!("{}No arrays found", indent);
+        return;
+    }
+
+    let column_widths = util::column_widths(
+        &header,
+        entries.iter().map(|entry| {
+            vec![
+                entry.name.len(),
+                entry.kind.len(),
+                entry.state.len(),
+                entry.detail.len(),
+                entry.progress.len(),
+            ]
+        }),
+    );
+
+    util::print_row(header, &column_widths, Some(indent));
+    entries.iter().for_each(|entry| {
+        let formatted_cells = util::format_width(
+            [
+                entry.name.as_str(),
+                entry.kind.as_str(),
+                entry.state.as_str(),
+                entry.detail.as_str(),
+                entry.progress.as_str(),
+            ],
+            &column_widths,
+        );
+        let state_colour = match entry.degraded {
+            true => Color::Red,
+            false => Color::Green,
+        };
+        let colours = [
+            None,
+            None,
+            Some(state_colour),
+            None,
+            match entry.progress.is_empty() {
+                true => None,
+                false => Some(Color::Yellow),
+            },
+        ];
+        util::print_row_colour(formatted_cells, colours, Some(indent))
+    });
+}
+
+#[cfg(test)]
+mod test {
+    use crate::components::raid;
+
+    const MDSTAT: &str = "Personalities : [raid1] [raid6] [raid5] [raid4]
+md0 : active raid1 sdb1[1] sda1[0]
+      1953382464 blocks super 1.2 [2/2] [UU]
+      bitmap: 0/15 pages [0KB], 65536KB chunk
+
+md1 : active raid1 sdd1[1](F) sdc1[0]
+      976630464 blocks super 1.2 [2/1] [U_]
+      [=>...................]  recovery =  8.5% (83328000/976630464) finish=80.3min speed=185344K/sec
+
+md127 : inactive sde[0](S)
+      976630464 blocks super 1.2
+
+unused devices: <none>
+";
+
+    #[test]
+    fn test_parse_mdstat() {
+        let entries = raid::parse_mdstat(MDSTAT);
+        assert_eq!(entries.len(), 3);
+
+        assert_eq!(entries[0].name, "md0");
+        assert_eq!(entries[0].kind, "raid1");
+        assert_eq!(entries[0].state, "active");
+        assert_eq!(entries[0].detail, "2/2 [UU]");
+        assert_eq!(entries[0].progress, "");
+        assert!(!entries[0].degraded);
+
+        assert_eq!(entries[1].state, "degraded");
+        assert_eq!(entries[1].detail, "1/2 [U_], 1 failed");
+        assert_eq!(entries[1].progress, "recovery 8.5% (finish 80.3min)");
+        assert!(entries[1].degraded);
+
+        assert_eq!(entries[2].name, "md127");
+        assert_eq!(entries[2].kind, "md");
+        assert_eq!(entries[2].state, "inactive");
+        assert!(entries[2].degraded);
+
+        assert!(raid::parse_mdstat("Personalities : \nunused devices: <none>\n").is_empty());
+    }
+
+    #[test]
+    fn test_parse_zpool_list() {
+        let entries = raid::parse_zpool_list("tank\tONLINE\t45%\nbackup\tDEGRADED\t80%\n");
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].state, "ONLINE");
+        assert_eq!(entries[0].detail, "45% used");
+        assert!(!entries[0].degraded);
+        assert!(entries[1].degraded);
+    }
+
+    #[test]
+    fn test_parse_btrfs_device_stats() {
+        let output = "[/dev/sda1].write_io_errs    0
+[/dev/sda1].read_io_errs     0
+[/dev/sdb1].write_io_errs    0
+[/dev/sdb1].corruption_errs  3
+";
+        let entry = raid::parse_btrfs_device_stats("/srv", output);
+        assert_eq!(entry.state, "errors");
+        assert_eq!(entry.detail, "[/dev/sdb1].corruption_errs 3");
+        assert!(entry.degraded);
+
+        let entry = raid::parse_btrfs_device_stats("/srv", "[/dev/sda1].write_io_errs    0\n");
+        assert_eq!(entry.state, "OK");
+        assert!(!entry.degraded);
+    }
+}
@@ -0,0 +1,175 @@
+use crate::config::{ReachabilityConfig, ReachabilityTargetConfig};
+use crate::util;
+use colored::Color;
+use std::io;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Addresses given as just a port number are checked on the local host.
+fn socket_address(address: &str) -> String {
+    match address.parse::<u16>() {
+        Ok(port) => format!("localhost:{}", port),
+        Err(_) => address.to_string(),
+    }
+}
+
+/// Connect to each resolved address in turn, returning the time taken by the first successful connection.
+fn connect(address: &str, timeout: Duration) -> io::Result<Duration> {
+    let unknown_host = || io::Error::new(io::ErrorKind::NotFound, "Unknown host");
+    let mut last_error = unknown_host();
+    for socket_address in address.to_socket_addrs().map_err(|_| unknown_host())? {
+        let start = Instant::now();
+        match TcpStream::connect_timeout(&socket_address, timeout) {
+            Ok(_) => return Ok(start.elapsed()),
+            Err(e) => last_error = e,
+        }
+    }
+    Err(last_error)
+}
+
+fn describe_error(error: &io::Error) -> String {
+    match error.kind() {
+        io::ErrorKind::ConnectionRefused => "Refused".to_string(),
+        io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock => "Timed out".to_string(),
+        _ => error.to_string(),
+    }
+}
+
+#[derive(Debug)]
+struct Entry {
+    name: String,
+    address: String,
+    /// Time to connect or the reason the target is down.
+    latency: Result<Duration, String>,
+}
+
+/// Check all targets in parallel. Targets which have not connected within their timeout,
+/// including while resolving their address, are shown as timed out.
+fn check_targets(targets: &[ReachabilityTargetConfig]) -> Vec<Entry> {
+    let start = Instant::now();
+    let receivers = targets
+        .iter()
+        .map(|target| {
+            let (sender, receiver) = mpsc::channel();
+            let address = socket_address(&target.address);
+            let timeout = Duration::from_millis(target.timeout_ms);
+            thread::spawn(move || sender.send(connect(&address, timeout)));
+            receiver
+        })
+        .collect::<Vec<_>>();
+
+    targets
+        .iter()
+        .zip(receivers)
+        .map(|(target, receiver)| {
+            let deadline = start + Duration::from_millis(target.timeout_ms);
+            let latency =
+                match receiver.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                    Ok(result) => result.map_err(|e| describe_error(&e)),
+                    Err(_) => Err("Timed out".to_string()),
+                };
+            Entry {
+                name: target
+                    .name
+                    .clone()
+                    .unwrap_or_else(|| target.address.clone()),
+                address: target.address.clone(),
+                latency,
+            }
+        })
+        .collect()
+}
+
+fn latency_ms(latency: &Duration) -> f64 {
+    latency.as_secs_f64() * 1000.0
+}
+
+pub fn print_reachability(cfg: &ReachabilityConfig, indent: &str) {
+    let header = ["Target", "Address", "State", "Latency"];
+
+    let entries = check_targets(&cfg.targets);
+    let formatted_entries = entries
+        .iter()
+        .map(|entry| {
+            let (state, latency) = match &entry.latency {
+                Ok(latency) => ("Up".to_string(), format!("{:.1} ms", latency_ms(latency))),
+                Err(error) => ("Down".to_string(), error.clone()),
+            };
+            [entry.name.clone(), entry.address.clone(), state, latency]
+        })
+        .collect::<Vec<[String; 4]>>();
+
+    let column_widths = util::column_widths(
+        &header,
+        formatted_entries
+            .iter()
+            .map(|cells| cells.iter().map(|cell| cell.chars().count()).collect()),
+    );
+
+    println!("Reachability:");
+    util::print_row(header, &column_widths, Some(indent));
+    entries
+        .iter()
+        .zip(formatted_entries.iter())
+        .for_each(|(entry, cells)| {
+            let formatted_cells =
+                util::format_width(cells.iter().map(|cell| cell.as_str()), &column_widths);
+            let colours = match &entry.latency {
+                Ok(latency) => [
+                    None,
+                    None,
+                    Some(Color::Green),
+                    Some(util::select_colour_number(
+                        latency_ms(latency),
+                        &cfg.latency,
+                    )),
+                ],
+                Err(_) => [None, None, Some(Color::Red), Some(Color::Red)],
+            };
+            util::print_row_colour(formatted_cells, colours, Some(indent))
+        });
+}
+
+#[cfg(test)]
+mod test {
+    use crate::components::reachability;
+    use crate::config::ReachabilityTargetConfig;
+    use std::net::TcpListener;
+
+    fn target(address: &str) -> ReachabilityTargetConfig {
+        serde_json::from_str(&format!(r#"{{"address": "{}"}}"#, address)).unwrap()
+    }
+
+    #[test]
+    fn test_check_targets() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let open_port = listener.local_addr().unwrap().port();
+        let closed_port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+
+        let mut named = target(&format!("127.0.0.1:{}", open_port));
+        named.name = Some("Database".to_string());
+        let entries = reachability::check_targets(&[
+            named,
+            target(&format!("127.0.0.1:{}", closed_port)),
+            target("not a valid address"),
+        ]);
+
+        assert_eq!(entries[0].name, "Database");
+        assert!(entries[0].latency.is_ok());
+        assert_eq!(entries[1].name, format!("127.0.0.1:{}", closed_port));
+        assert_eq!(entries[1].latency, Err("Refused".to_string()));
+        assert_eq!(entries[2].latency, Err("Unknown host".to_string()));
+    }
+
+    #[test]
+    fn test_socket_address() {
+        assert_eq!(reachability::socket_address("5432"), "localhost:5432");
+        assert_eq!(reachability::socket_address("nas.lan:445"), "nas.lan:445");
+    }
+}
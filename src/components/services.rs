@@ -1,4 +1,5 @@
-use crate::config::ServiceConfig;
+use crate::config::{ServiceConfig, UnitsConfig};
+use crate::units;
 use crate::util;
 use bytesize::ByteSize;
 use colored::*;
@@ -52,18 +53,23 @@ pub fn format_service_state(active_state: &str, sub_state: &str, width: usize) -
         .to_string()
 }
 
-pub fn format_mem_current(mem_current: &Option<ByteSize>, cfg: &ServiceConfig) -> String {
+pub fn format_mem_current(
+    mem_current: &Option<ByteSize>,
+    cfg: &ServiceConfig,
+    units_cfg: &UnitsConfig,
+) -> String {
     let mem_colour = match (&cfg.memory_usage, mem_current) {
         (Some(mem_usage_cond), Some(mem_current)) => {
             util::select_colour_number(mem_current.as_u64(), mem_usage_cond)
         }
         _ => Color::White,
     };
-    mem_current
-        .unwrap_or(ByteSize::b(0))
-        .to_string()
-        .color(mem_colour)
-        .to_string()
+    units::format_bytes(
+        mem_current.unwrap_or(ByteSize::b(0)).as_u64(),
+        &units_cfg.bytes,
+    )
+    .color(mem_colour)
+    .to_string()
 }
 
 pub fn systemd_show(service_names: &[&str]) -> String {
@@ -81,7 +87,7 @@ pub fn systemd_show(service_names: &[&str]) -> String {
     output
 }
 
-pub fn print_services(cfg: &HashMap<String, ServiceConfig>, indent: &str) {
+pub fn print_services(cfg: &HashMap<String, ServiceConfig>, units_cfg: &UnitsConfig, indent: &str) {
     let header = ["Service", "Status", "Memory Usage"];
 
     let mut ordered_service_names = cfg.keys().map(|name| name.as_str()).collect::<Vec<&str>>();
@@ -107,11 +113,11 @@ pub fn print_services(cfg: &HashMap<String, ServiceConfig>, indent: &str) {
             vec![
                 entry.service_name.len(),
                 entry.active_state.len() + entry.sub_state.len() + 3,
-                entry
-                    .mem_current
-                    .unwrap_or(ByteSize::b(0))
-                    .to_string()
-                    .len(),
+                units::format_bytes(
+                    entry.mem_current.unwrap_or(ByteSize::b(0)).as_u64(),
+                    &units_cfg.bytes,
+                )
+                .len(),
             ]
         }),
     );
@@ -126,7 +132,7 @@ pub fn print_services(cfg: &HashMap<String, ServiceConfig>, indent: &str) {
             [
                 entry.service_name,
                 &format_service_state(entry.active_state, entry.sub_state, column_widths[1]),
-                &format_mem_current(&entry.mem_current, service_cfg),
+                &format_mem_current(&entry.mem_current, service_cfg, units_cfg),
             ],
             &column_widths,
             Some(indent),
@@ -0,0 +1,216 @@
+use crate::config::StorageHistoryConfig;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::env;
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process;
+
+const SECONDS_PER_DAY: f64 = 24.0 * 60.0 * 60.0;
+
+/// Usage of a mount at a point in time.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Sample {
+    /// Unix timestamp in seconds.
+    pub timestamp: i64,
+    /// Used space in bytes.
+    pub used: u64,
+}
+
+/// Usage samples of each mount point, stored between runs in the state file.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct History {
+    pub mounts: HashMap<String, Vec<Sample>>,
+}
+
+impl History {
+    /// Record a sample for a mount point unless the latest sample is more recent than `min_interval_secs`.
+    pub fn record(&mut self, mount_point: &str, sample: Sample, min_interval_secs: i64) {
+        let samples = self.mounts.entry(mount_point.to_string()).or_default();
+        match samples.last() {
+            Some(last) if sample.timestamp - last.timestamp < min_interval_secs => {}
+            _ => samples.push(sample),
+        }
+    }
+
+    /// Remove samples older than `oldest_timestamp` and mount points left without samples.
+    pub fn prune(&mut self, oldest_timestamp: i64) {
+        self.mounts.values_mut().for_each(|samples| {
+            samples.retain(|sample| sample.timestamp >= oldest_timestamp);
+        });
+        self.mounts.retain(|_, samples| !samples.is_empty());
+    }
+}
+
+/// Path of the state file, defaulting to `$XDG_STATE_HOME/sysstatus/storage_history.json`
+/// or `$HOME/.local/state/sysstatus/storage_history.json`.
+pub fn history_path(cfg: &StorageHistoryConfig) -> Result<PathBuf, env::VarError> {
+    match &cfg.path {
+        Some(path) => Ok(path.clone()),
+        None => {
+            let state_base = env::var("XDG_STATE_HOME")
+                .or_else(|_| env::var("HOME").map(|home| home + "/.local/state"))?;
+            Ok(Path::new(&state_base).join("sysstatus/storage_history.json"))
+        }
+    }
+}
+
+/// Load the history from the state file. A missing or unreadable file gives an empty history,
+/// as does a file which cannot be parsed after warning that its samples will be discarded.
+pub fn load(path: &Path) -> History {
+    let reader = match File::open(path) {
+        Ok(reader) => reader,
+        Err(_) => return History::default(),
+    };
+    serde_json::from_reader(reader).unwrap_or_else(|e| {
+        eprintln!(
+            "Failed to parse storage history \"{}\", starting a new history: {}",
+            path.to_string_lossy(),
+            e
+        );
+        History::default()
+    })
+}
+
+/// Save the history to the state file. The history is written to a temporary file in the same directory which then
+/// replaces the state file, so an interrupted save leaves the previous history intact. The temporary file is named
+/// after the process so that runs at the same time, e.g. from simultaneous logins, do not write to the same file.
+pub fn save(path: &Path, history: &History) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut temp_name = path.file_name().unwrap_or_default().to_os_string();
+    temp_name.push(format!(".{}.tmp", process::id()));
+    let temp_path = path.with_file_name(temp_name);
+
+    let result = File::create(&temp_path).and_then(|writer| {
+        serde_json::to_writer(&writer, history)?;
+        writer.sync_all()?;
+        fs::rename(&temp_path, path)
+    });
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result
+}
+
+/// Growth of the used space in bytes per day, calculated by least squares linear regression over the samples.
+///
+/// Returns `None` if there are fewer than two samples or they all share the same timestamp.
+pub fn growth_per_day(samples: &[Sample]) -> Option<f64> {
+    if samples.len() < 2 {
+        return None;
+    }
+    let count = samples.len() as f64;
+    let mean_time = samples.iter().map(|s| s.timestamp as f64).sum::<f64>() / count;
+    let mean_used = samples.iter().map(|s| s.used as f64).sum::<f64>() / count;
+
+    let (covariance, variance) = samples.iter().fold((0.0, 0.0), |(cov, var), sample| {
+        let time_diff = sample.timestamp as f64 - mean_time;
+        (
+            cov + time_diff * (sample.used as f64 - mean_used),
+            var + time_diff * time_diff,
+        )
+    });
+    if variance == 0.0 {
+        return None;
+    }
+    Some(covariance / variance * SECONDS_PER_DAY)
+}
+
+/// Estimate the number of days until a mount with `free` bytes remaining is full.
+///
+/// Returns `None` if usage is not growing.
+pub fn days_until_full(samples: &[Sample], free: u64) -> Option<f64> {
+    match growth_per_day(samples) {
+        Some(growth) if growth > 0.0 => Some(free as f64 / growth),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::components::storage_history::{self, History, Sample};
+    use std::fs;
+    use std::process;
+
+    const DAY: i64 = 24 * 60 * 60;
+
+    fn sample(day: i64, used: u64) -> Sample {
+        Sample {
+            timestamp: day * DAY,
+            used,
+        }
+    }
+
+    #[test]
+    fn test_growth_per_day() {
+        assert_eq!(storage_history::growth_per_day(&[]), None);
+        assert_eq!(storage_history::growth_per_day(&[sample(0, 10)]), None);
+        assert_eq!(
+            storage_history::growth_per_day(&[sample(1, 10), sample(1, 20)]),
+            None
+        );
+        assert_eq!(
+            storage_history::growth_per_day(&[sample(0, 100), sample(1, 200), sample(2, 300)]),
+            Some(100.0)
+        );
+        assert_eq!(
+            storage_history::growth_per_day(&[
+                sample(0, 100),
+                sample(2, 80),
+                sample(4, 110),
+                sample(6, 90)
+            ]),
+            Some(0.0)
+        );
+    }
+
+    #[test]
+    fn test_days_until_full() {
+        let growing = [sample(0, 100), sample(1, 200), sample(2, 300)];
+        assert_eq!(storage_history::days_until_full(&growing, 500), Some(5.0));
+        let shrinking = [sample(0, 300), sample(1, 200)];
+        assert_eq!(storage_history::days_until_full(&shrinking, 500), None);
+    }
+
+    #[test]
+    fn test_record_and_prune() {
+        let mut history = History::default();
+        history.record("/", sample(0, 1), 3600);
+        history.record(
+            "/",
+            Sample {
+                timestamp: 60,
+                used: 2,
+            },
+            3600,
+        );
+        history.record("/", sample(1, 3), 3600);
+        history.record("/srv", sample(0, 4), 3600);
+        assert_eq!(history.mounts["/"], vec![sample(0, 1), sample(1, 3)]);
+
+        history.prune(DAY);
+        assert_eq!(history.mounts["/"], vec![sample(1, 3)]);
+        assert!(!history.mounts.contains_key("/srv"));
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let dir = std::env::temp_dir().join(format!("sysstatus-history-{}", process::id()));
+        let path = dir.join("storage_history.json");
+        let _ = fs::remove_dir_all(&dir);
+        assert!(storage_history::load(&path).mounts.is_empty());
+
+        let mut history = History::default();
+        history.record("/", sample(1, 3), 3600);
+        storage_history::save(&path, &history).unwrap();
+        assert_eq!(storage_history::load(&path).mounts["/"], vec![sample(1, 3)]);
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+
+        fs::write(&path, "{\"mounts\": {\"/\": [").unwrap();
+        assert!(storage_history::load(&path).mounts.is_empty());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
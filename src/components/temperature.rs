@@ -1,39 +1,369 @@
-use crate::config::ConditionalColour;
+use crate::config::{TemperatureConfig, TemperatureUnit};
+use crate::units;
 use crate::util::select_colour_number;
 use colored::*;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
 use sysinfo::{Component, ComponentExt};
 use sysinfo::{System, SystemExt};
 
-pub fn single_sensor(
-    component: &Component,
-    cfg: &ConditionalColour<f32>,
+const HWMON_PATH: &str = "/sys/class/hwmon";
+
+/// Temperature shown on a single line, either of a sensor or the hottest sensor of a group.
+#[derive(Debug, PartialEq)]
+struct Reading {
+    name: String,
+    temperature: f32,
+    /// Maximum temperature reported by the hardware.
+    max: Option<f32>,
+    /// Critical temperature reported by the hardware.
+    critical: Option<f32>,
+}
+
+impl Reading {
+    fn new(component: &Component, maxima: &HashMap<String, f32>) -> Reading {
+        Reading {
+            name: component.label().to_string(),
+            temperature: component.temperature(),
+            max: maxima.get(component.label()).copied(),
+            critical: component.critical(),
+        }
+    }
+}
+
+/// Read the maximum temperature in °C of each labelled hwmon sensor, keyed by label as sysinfo names them.
+/// sysinfo raises the maximum of a component to the highest temperature seen, which hides the hardware maximum
+/// as soon as a sensor reaches it. Labels shared by sensors with different maxima cannot be told apart so are left out.
+fn hardware_maxima(hwmon_path: &Path) -> HashMap<String, f32> {
+    let mut maxima: HashMap<String, Option<f32>> = HashMap::new();
+    let chips = fs::read_dir(hwmon_path).into_iter().flatten().flatten();
+    for chip in chips {
+        let files = fs::read_dir(chip.path()).into_iter().flatten().flatten();
+        for file in files {
+            let file_name = file.file_name().to_string_lossy().to_string();
+            let sensor = match file_name.strip_suffix("_label") {
+                Some(sensor) if sensor.starts_with("temp") => sensor,
+                _ => continue,
+            };
+            let label = match fs::read_to_string(file.path()) {
+                Ok(label) => label.trim().to_string(),
+                Err(_) => continue,
+            };
+            let max = fs::read_to_string(chip.path().join(format!("{}_max", sensor)))
+                .ok()
+                .and_then(|max| max.trim().parse::<f32>().ok())
+                .map(|max| max / 1000.0);
+            match maxima.entry(label) {
+                Entry::Vacant(entry) => {
+                    entry.insert(max);
+                }
+                Entry::Occupied(mut entry) if *entry.get() != max => {
+                    entry.insert(None);
+                }
+                Entry::Occupied(_) => {}
+            }
+        }
+    }
+    maxima
+        .into_iter()
+        .filter_map(|(label, max)| Some((label, max?)))
+        .collect()
+}
+
+fn is_shown(label: &str, cfg: &TemperatureConfig) -> bool {
+    (cfg.include.is_empty() || cfg.include.iter().any(|pattern| pattern.matches(label)))
+        && !cfg.exclude.iter().any(|pattern| pattern.matches(label))
+}
+
+/// Filter, rename and group the readings of each sensor, which are named by the sensor label.
+/// Groups are placed at the position of their first sensor.
+fn readings(sensors: impl IntoIterator<Item = Reading>, cfg: &TemperatureConfig) -> Vec<Reading> {
+    let mut readings: Vec<Reading> = Vec::new();
+    for mut sensor in sensors {
+        if !is_shown(&sensor.name, cfg) {
+            continue;
+        }
+        let group = cfg.groups.iter().find(|group| {
+            group
+                .sensors
+                .iter()
+                .any(|pattern| pattern.matches(&sensor.name))
+        });
+        match group {
+            Some(group) => {
+                sensor.name = group.name.clone();
+                match readings
+                    .iter_mut()
+                    .find(|reading| reading.name == group.name)
+                {
+                    Some(reading) if reading.temperature >= sensor.temperature => {}
+                    Some(reading) => *reading = sensor,
+                    None => readings.push(sensor),
+                }
+            }
+            None => {
+                if let Some(alias) = cfg.aliases.get(&sensor.name) {
+                    sensor.name = alias.clone();
+                }
+                readings.push(sensor);
+            }
+        }
+    }
+    readings
+}
+
+/// Select the colour of a reading. Colouring of the sensor takes precedence, followed by colouring relative
+/// to the critical temperature, colouring relative to the maximum temperature and then the absolute colouring.
+/// All comparisons are made in the configured unit.
+fn reading_colour(reading: &Reading, cfg: &TemperatureConfig, unit: &TemperatureUnit) -> Color {
+    let convert = |celsius: f32| units::convert_temperature(celsius, unit);
+    let temperature = convert(reading.temperature);
+    if let Some(colouring) = cfg.sensors.get(&reading.name) {
+        return select_colour_number(temperature, colouring);
+    }
+    if let (Some(critical), Some(colouring)) = (reading.critical, &cfg.critical_colouring) {
+        return select_colour_number(temperature - convert(critical), colouring);
+    }
+    match (reading.max, &cfg.max_colouring) {
+        (Some(max), Some(colouring)) => select_colour_number(temperature - convert(max), colouring),
+        _ => select_colour_number(temperature, &cfg.colouring),
+    }
+}
+
+fn single_sensor(
+    reading: &Reading,
+    cfg: &TemperatureConfig,
+    unit: &TemperatureUnit,
     align_length: usize,
 ) -> String {
-    let coloured_temp = component
-        .temperature()
-        .to_string()
-        .color(select_colour_number(component.temperature(), cfg));
+    let coloured_temp = units::temperature_value(reading.temperature, unit)
+        .color(reading_colour(reading, cfg, unit));
+    let critical = match (cfg.show_critical, reading.critical) {
+        (true, Some(critical)) => {
+            format!(" (critical {})", units::format_temperature(critical, unit))
+        }
+        _ => String::new(),
+    };
     format!(
-        "    {:.<algn$}: {}°C",
-        component.label(),
+        "    {:.<algn$}: {}{}{}",
+        reading.name,
         coloured_temp,
+        units::temperature_symbol(unit),
+        critical,
         algn = align_length
     )
 }
 
-pub fn temperature(sys: &mut System, cfg: &ConditionalColour<f32>) -> String {
+pub fn temperature(sys: &mut System, cfg: &TemperatureConfig, unit: &TemperatureUnit) -> String {
     sys.refresh_components_list();
-    let align_length = sys
-        .components()
+    let maxima = hardware_maxima(Path::new(HWMON_PATH));
+    let readings = readings(
+        sys.components()
+            .iter()
+            .map(|component| Reading::new(component, &maxima)),
+        cfg,
+    );
+    let align_length = readings
         .iter()
-        .map(|component| component.label().len())
+        .map(|reading| reading.name.len())
         .max()
         .unwrap_or(0)
         + 3;
 
-    sys.components()
+    readings
         .iter()
-        .map(|component| single_sensor(&component, cfg, align_length))
+        .map(|reading| single_sensor(reading, cfg, unit, align_length))
         .collect::<Vec<String>>()
         .join("\n")
 }
+
+#[cfg(test)]
+mod test {
+    use crate::components::temperature::{self, Reading};
+    use crate::config::{TemperatureConfig, TemperatureUnit};
+    use colored::Color;
+    use std::fs;
+    use std::path::Path;
+    use std::process;
+
+    fn reading(name: &str, temperature: f32) -> Reading {
+        Reading {
+            name: name.to_string(),
+            temperature,
+            max: None,
+            critical: None,
+        }
+    }
+
+    #[test]
+    fn test_readings() {
+        let cfg: TemperatureConfig = serde_json::from_str(
+            r#"{
+                "default_colour": "Green",
+                "levels": [],
+                "exclude": ["edge"],
+                "aliases": {"Package id 0": "CPU"},
+                "groups": [
+                    {"name": "Cores", "sensors": ["Core *"]},
+                    {"name": "NVMe", "sensors": ["Composite", "Sensor *"]}
+                ]
+            }"#,
+        )
+        .unwrap();
+        let sensors = || {
+            vec![
+                reading("edge", 27.8),
+                reading("Package id 0", 51.0),
+                reading("Core 0", 47.0),
+                reading("Core 1", 53.0),
+                reading("Composite", 38.85),
+                reading("Core 2", 49.0),
+                reading("Sensor 1", 41.85),
+            ]
+        };
+        assert_eq!(
+            temperature::readings(sensors(), &cfg),
+            vec![
+                reading("CPU", 51.0),
+                reading("Cores", 53.0),
+                reading("NVMe", 41.85)
+            ]
+        );
+
+        let cfg: TemperatureConfig = serde_json::from_str(
+            r#"{"default_colour": "Green", "levels": [], "include": ["Composite", "Sensor *"]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            temperature::readings(sensors(), &cfg),
+            vec![reading("Composite", 38.85), reading("Sensor 1", 41.85)]
+        );
+    }
+
+    #[test]
+    fn test_reading_colour() {
+        let cfg: TemperatureConfig = serde_json::from_str(
+            r#"{
+                "default_colour": "Green",
+                "levels": [{"min": 70, "colour": "Red"}],
+                "critical_colouring": {
+                    "default_colour": "Green",
+                    "levels": [{"min": -15, "colour": "Yellow"}, {"min": -5, "colour": "Red"}]
+                },
+                "max_colouring": {
+                    "default_colour": "Green",
+                    "levels": [{"min": -10, "colour": "Red"}]
+                },
+                "sensors": {
+                    "GPU": {"default_colour": "Blue", "levels": []}
+                }
+            }"#,
+        )
+        .unwrap();
+        let disk = Reading {
+            critical: Some(60.0),
+            ..reading("Disk", 50.0)
+        };
+        assert_eq!(
+            temperature::reading_colour(&disk, &cfg, &TemperatureUnit::Celsius),
+            Color::Yellow
+        );
+        let soc = Reading {
+            critical: Some(85.0),
+            max: Some(80.0),
+            ..reading("SoC", 50.0)
+        };
+        assert_eq!(
+            temperature::reading_colour(&soc, &cfg, &TemperatureUnit::Celsius),
+            Color::Green
+        );
+        let board = Reading {
+            max: Some(55.0),
+            ..reading("Board", 50.0)
+        };
+        assert_eq!(
+            temperature::reading_colour(&board, &cfg, &TemperatureUnit::Celsius),
+            Color::Red
+        );
+        assert_eq!(
+            temperature::reading_colour(&reading("Other", 50.0), &cfg, &TemperatureUnit::Celsius),
+            Color::Green
+        );
+        assert_eq!(
+            temperature::reading_colour(&reading("GPU", 90.0), &cfg, &TemperatureUnit::Celsius),
+            Color::Blue
+        );
+
+        // Thresholds are in the configured unit so 50°C is 122°F, above the 70 degree level.
+        assert_eq!(
+            temperature::reading_colour(
+                &reading("Other", 50.0),
+                &cfg,
+                &TemperatureUnit::Fahrenheit
+            ),
+            Color::Red
+        );
+        // Differences are also in the configured unit so 10°C below critical is 18°F below.
+        let disk = Reading {
+            critical: Some(60.0),
+            ..reading("Disk", 50.0)
+        };
+        assert_eq!(
+            temperature::reading_colour(&disk, &cfg, &TemperatureUnit::Fahrenheit),
+            Color::Green
+        );
+    }
+
+    #[test]
+    fn test_hardware_maxima() {
+        let hwmon = std::env::temp_dir().join(format!("sysstatus-hwmon-{}", process::id()));
+        let _ = fs::remove_dir_all(&hwmon);
+        let write = |chip: &str, file: &str, contents: &str| {
+            fs::create_dir_all(hwmon.join(chip)).unwrap();
+            fs::write(hwmon.join(chip).join(file), contents).unwrap();
+        };
+        // A sensor above its maximum, which sysinfo would report with the maximum raised to the temperature.
+        write("hwmon0", "temp1_label", "Package id 0\n");
+        write("hwmon0", "temp1_input", "85000\n");
+        write("hwmon0", "temp1_max", "80000\n");
+        write("hwmon0", "temp2_label", "Core 0\n");
+        write("hwmon0", "temp2_input", "80000\n");
+        write("hwmon0", "temp2_max", "80000\n");
+        write("hwmon0", "temp3_label", "No max\n");
+        write("hwmon1", "temp1_label", "Composite\n");
+        write("hwmon1", "temp1_max", "84850\n");
+        write("hwmon2", "temp1_label", "Composite\n");
+        write("hwmon2", "temp1_max", "79850\n");
+
+        let maxima = temperature::hardware_maxima(Path::new(&hwmon));
+        fs::remove_dir_all(&hwmon).unwrap();
+        assert_eq!(maxima.len(), 2);
+        assert_eq!(maxima.get("Package id 0"), Some(&80.0));
+        assert_eq!(maxima.get("Core 0"), Some(&80.0));
+
+        // Sensors at or above their maximum are still coloured relative to it rather than absolutely.
+        let cfg: TemperatureConfig = serde_json::from_str(
+            r#"{
+                "default_colour": "Green",
+                "levels": [{"min": 90, "colour": "Red"}],
+                "max_colouring": {
+                    "default_colour": "Green",
+                    "levels": [{"min": 0, "colour": "Red"}]
+                }
+            }"#,
+        )
+        .unwrap();
+        for (name, temp) in [("Package id 0", 85.0), ("Core 0", 80.0)] {
+            let sensor = Reading {
+                max: maxima.get(name).copied(),
+                ..reading(name, temp)
+            };
+            assert_eq!(
+                temperature::reading_colour(&sensor, &cfg, &TemperatureUnit::Celsius),
+                Color::Red
+            );
+        }
+    }
+}
@@ -0,0 +1,373 @@
+use crate::config::{TimeFormatConfig, UpsConfig};
+use crate::time_format;
+use crate::util;
+use colored::Color;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+enum NutError {
+    #[error("Could not resolve {0}")]
+    UnknownAddress(String),
+
+    #[error("upsd returned {0}")]
+    Server(String),
+
+    #[error("Unexpected response from upsd: {0}")]
+    UnexpectedResponse(String),
+
+    #[error(transparent)]
+    IOError(#[from] io::Error),
+}
+
+/// Connection to a Network UPS Tools `upsd` using its line based text protocol.
+struct NutClient {
+    reader: BufReader<TcpStream>,
+}
+
+impl NutClient {
+    fn connect(address: &str, timeout: Duration) -> Result<NutClient, NutError> {
+        // Every resolved address is tried in turn as e.g. `localhost` resolves to both `::1` and `127.0.0.1`
+        // while upsd may only listen on one of them.
+        let mut last_error = NutError::UnknownAddress(address.to_string());
+        let mut stream = None;
+        for socket_address in address.to_socket_addrs()? {
+            match TcpStream::connect_timeout(&socket_address, timeout) {
+                Ok(connected) => {
+                    stream = Some(connected);
+                    break;
+                }
+                Err(e) => last_error = e.into(),
+            }
+        }
+        let stream = stream.ok_or(last_error)?;
+        stream.set_read_timeout(Some(timeout))?;
+        stream.set_write_timeout(Some(timeout))?;
+        Ok(NutClient {
+            reader: BufReader::new(stream),
+        })
+    }
+
+    fn read_line(&mut self) -> Result<String, NutError> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            return Err(NutError::UnexpectedResponse(
+                "connection closed".to_string(),
+            ));
+        }
+        Ok(line.trim_end().to_string())
+    }
+
+    /// Send a `LIST` command e.g. `LIST VAR myups` and return the lines between `BEGIN LIST` and `END LIST`.
+    fn list(&mut self, query: &str) -> Result<Vec<String>, NutError> {
+        writeln!(self.reader.get_mut(), "LIST {}", query)?;
+
+        let begin = self.read_line()?;
+        if let Some(error) = begin.strip_prefix("ERR ") {
+            return Err(NutError::Server(error.to_string()));
+        }
+        if begin != format!("BEGIN LIST {}", query) {
+            return Err(NutError::UnexpectedResponse(begin));
+        }
+
+        let end = format!("END LIST {}", query);
+        let mut lines = Vec::new();
+        loop {
+            let line = self.read_line()?;
+            if line == end {
+                return Ok(lines);
+            }
+            lines.push(line);
+        }
+    }
+}
+
+/// Split a line of the form `VAR myups battery.charge "100"` into the words before the quoted value
+/// and the unescaped value.
+fn parse_list_line(line: &str) -> Option<(Vec<&str>, String)> {
+    let (words, quoted) = line.split_once(" \"")?;
+    let quoted = quoted.strip_suffix('"')?;
+
+    let mut value = String::with_capacity(quoted.len());
+    let mut chars = quoted.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => value.extend(chars.next()),
+            c => value.push(c),
+        }
+    }
+    Some((words.split(' ').collect(), value))
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct Entry {
+    name: String,
+    /// Status flags from `ups.status` e.g. `OL CHRG`.
+    status: Option<String>,
+    /// Battery charge as a percentage.
+    charge: Option<u64>,
+    /// Battery runtime in seconds.
+    runtime: Option<u64>,
+    /// Load as a percentage of the rated power.
+    load: Option<u64>,
+    error: Option<String>,
+}
+
+fn entry(name: &str, vars: &[String]) -> Entry {
+    let mut entry = Entry {
+        name: name.to_string(),
+        ..Entry::default()
+    };
+    for (words, value) in vars.iter().filter_map(|line| parse_list_line(line)) {
+        let number = || {
+            value
+                .parse::<f64>()
+                .ok()
+                .map(|number| number.round() as u64)
+        };
+        match words[..] {
+            ["VAR", _, "ups.status"] => entry.status = Some(value.clone()),
+            ["VAR", _, "battery.charge"] => entry.charge = number(),
+            ["VAR", _, "battery.runtime"] => entry.runtime = number(),
+            ["VAR", _, "ups.load"] => entry.load = number(),
+            _ => {}
+        }
+    }
+    entry
+}
+
+fn ups_names(client: &mut NutClient) -> Result<Vec<String>, NutError> {
+    Ok(client
+        .list("UPS")?
+        .iter()
+        .filter_map(|line| match parse_list_line(line)?.0[..] {
+            ["UPS", name] => Some(name.to_string()),
+            _ => None,
+        })
+        .collect())
+}
+
+fn read_entries(cfg: &UpsConfig) -> Result<Vec<Entry>, NutError> {
+    let mut client = NutClient::connect(&cfg.address, Duration::from_millis(cfg.timeout_ms))?;
+    let names = match cfg.names.is_empty() {
+        true => ups_names(&mut client)?,
+        false => cfg.names.clone(),
+    };
+    Ok(names
+        .iter()
+        .map(|name| match client.list(&format!("VAR {}", name)) {
+            Ok(vars) => entry(name, &vars),
+            Err(e) => Entry {
+                name: name.clone(),
+                error: Some(e.to_string()),
+                ..Entry::default()
+            },
+        })
+        .collect())
+}
+
+/// Describe the status flags of a UPS, most important first.
+fn format_status(status: &str) -> String {
+    let flags = status.split_whitespace().collect::<Vec<&str>>();
+    let descriptions = [
+        ("FSD", "Forced shutdown"),
+        ("OB", "On battery"),
+        ("OL", "On line"),
+        ("LB", "Low battery"),
+        ("RB", "Replace battery"),
+        ("OVER", "Overloaded"),
+        ("BYPASS", "Bypass"),
+        ("CHRG", "Charging"),
+    ];
+    let described = descriptions
+        .iter()
+        .filter(|(flag, _)| flags.contains(flag))
+        .map(|(_, description)| *description)
+        .collect::<Vec<&str>>();
+    match described.is_empty() {
+        true => status.to_string(),
+        false => described.join(", "),
+    }
+}
+
+fn status_colour(status: &str) -> Color {
+    let flags = status.split_whitespace().collect::<Vec<&str>>();
+    if ["FSD", "LB", "RB", "OVER"]
+        .iter()
+        .any(|flag| flags.contains(flag))
+    {
+        Color::Red
+    } else if flags.contains(&"OL") && !flags.contains(&"OB") {
+        Color::Green
+    } else {
+        Color::Yellow
+    }
+}
+
+fn format_optional(value: Option<u64>) -> String {
+    value
+        .map(|value| value.to_string())
+        .unwrap_or_else(|| "N/A".to_string())
+}
+
+pub fn print_ups(cfg: &UpsConfig, time_cfg: &TimeFormatConfig, indent: &str) {
+    let header = ["UPS", "Status", "Charge(%)", "Runtime", "Load(%)"];
+
+    println!("UPS:");
+    let entries = match read_entries(cfg) {
+        Ok(entries) => entries,
+        Err(e) => {
+            println!("{}Failed to query upsd at {}: {}", indent, cfg.address, e);
+            return;
+        }
+    };
+
+    let formatted_entries = entries
+        .iter()
+        .map(|entry| {
+            [
+                entry.name.clone(),
+                match (&entry.status, &entry.error) {
+                    (_, Some(error)) => error.clone(),
+                    (Some(status), None) => format_status(status),
+                    (None, None) => "N/A".to_string(),
+                },
+                format_optional(entry.charge),
+                entry
+                    .runtime
+                    .map(|seconds| time_format::format_duration(seconds, time_cfg.max_units))
+                    .unwrap_or_else(|| "N/A".to_string()),
+                format_optional(entry.load),
+            ]
+        })
+        .collect::<Vec<[String; 5]>>();
+
+    let column_widths = util::column_widths(
+        &header,
+        formatted_entries
+            .iter()
+            .map(|cells| cells.iter().map(|cell| cell.len()).collect()),
+    );
+
+    util::print_row(header, &column_widths, Some(indent));
+    entries
+        .iter()
+        .zip(formatted_entries.iter())
+        .for_each(|(entry, cells)| {
+            let formatted_cells =
+                util::format_width(cells.iter().map(|cell| cell.as_str()), &column_widths);
+            let colours = [
+                None,
+                match (&entry.status, &entry.error) {
+                    (_, Some(_)) => Some(Color::Red),
+                    (Some(status), None) => Some(status_colour(status)),
+                    (None, None) => None,
+                },
+                entry
+                    .charge
+                    .map(|charge| util::select_colour_below(charge, &cfg.charge)),
+                entry
+                    .runtime
+                    .map(|runtime| util::select_colour_below(runtime, &cfg.runtime)),
+                entry
+                    .load
+                    .map(|load| util::select_colour_number(load, &cfg.load)),
+            ];
+            util::print_row_colour(formatted_cells, colours, Some(indent))
+        });
+}
+
+#[cfg(test)]
+mod test {
+    use crate::components::ups::{self, Entry};
+    use crate::config::UpsConfig;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    /// Start a fake upsd on a random port which answers `LIST` commands for a single UPS named `rack`.
+    fn fake_upsd() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut writer = stream.try_clone().unwrap();
+            for line in BufReader::new(stream).lines() {
+                let response = match line.unwrap().as_str() {
+                    "LIST UPS" => "BEGIN LIST UPS\nUPS rack \"Rack \\\"A\\\" UPS\"\nEND LIST UPS\n",
+                    "LIST VAR rack" => {
+                        "BEGIN LIST VAR rack
+VAR rack battery.charge \"87\"
+VAR rack battery.runtime \"1830\"
+VAR rack device.mfr \"APC\"
+VAR rack ups.load \"23.6\"
+VAR rack ups.status \"OB DISCHRG\"
+END LIST VAR rack
+"
+                    }
+                    _ => "ERR UNKNOWN-UPS\n",
+                };
+                writer.write_all(response.as_bytes()).unwrap();
+            }
+        });
+        address
+    }
+
+    fn config(address: String, names: &[&str]) -> UpsConfig {
+        let mut cfg: UpsConfig = serde_json::from_str(
+            r#"{
+                "charge": {"default_colour": "Green", "levels": []},
+                "runtime": {"default_colour": "Green", "levels": []},
+                "load": {"default_colour": "Green", "levels": []}
+            }"#,
+        )
+        .unwrap();
+        cfg.address = address;
+        cfg.names = names.iter().map(|name| name.to_string()).collect();
+        cfg
+    }
+
+    #[test]
+    fn test_read_entries() {
+        let entries = ups::read_entries(&config(fake_upsd(), &[])).unwrap();
+        assert_eq!(
+            entries,
+            vec![Entry {
+                name: "rack".to_string(),
+                status: Some("OB DISCHRG".to_string()),
+                charge: Some(87),
+                runtime: Some(1830),
+                load: Some(24),
+                error: None,
+            }]
+        );
+
+        let entries = ups::read_entries(&config(fake_upsd(), &["rack", "missing"])).unwrap();
+        assert_eq!(entries[0].charge, Some(87));
+        assert_eq!(
+            entries[1].error.as_deref(),
+            Some("upsd returned UNKNOWN-UPS")
+        );
+    }
+
+    #[test]
+    fn test_parse_list_line() {
+        assert_eq!(
+            ups::parse_list_line(r#"UPS rack "Rack \"A\" UPS""#),
+            Some((vec!["UPS", "rack"], r#"Rack "A" UPS"#.to_string()))
+        );
+        assert_eq!(ups::parse_list_line("BEGIN LIST UPS"), None);
+    }
+
+    #[test]
+    fn test_format_status() {
+        assert_eq!(ups::format_status("OL CHRG"), "On line, Charging");
+        assert_eq!(
+            ups::format_status("OB DISCHRG LB"),
+            "On battery, Low battery"
+        );
+        assert_eq!(ups::format_status("WAIT"), "WAIT");
+    }
+}
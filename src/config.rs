@@ -1,6 +1,10 @@
+use chrono_tz::Tz;
 use colored::Color;
+use glob::Pattern;
+use regex::Regex;
 use serde::Deserialize;
 use std::collections::HashMap;
+use std::path::PathBuf;
 
 /// Configuration struct for the program
 #[derive(Debug, Deserialize)]
@@ -9,14 +13,46 @@ pub struct SysStatusConfig {
     pub general_info: Option<GeneralInfoConfig>,
     /// Configuration for the storage section.
     pub storage: Option<StorageConfig>,
+    /// Configuration for the file freshness section.
+    pub files: Option<FilesConfig>,
+    /// Configuration for the pressure stall information section.
+    pub pressure: Option<PressureConfig>,
+    /// Configuration for the kernel events section.
+    pub kernel: Option<KernelConfig>,
+    /// Configuration for the disk I/O section.
+    pub disk_io: Option<DiskIoConfig>,
+    /// Configuration for the SMART disk health section.
+    pub disk_health: Option<DiskHealthConfig>,
+    /// Configuration for the software RAID, ZFS and Btrfs array section.
+    pub raid: Option<RaidConfig>,
+    /// Configuration for the battery and power supply section.
+    pub power: Option<PowerConfig>,
+    /// Configuration for the UPS section.
+    pub ups: Option<UpsConfig>,
+    /// Configuration for the Raspberry Pi health section.
+    pub pi_health: Option<PiHealthConfig>,
     /// Configuration for the temperature section.
-    /// Conditionally colours the temperatures presented.
-    pub temperature: Option<ConditionalColour<f32>>,
+    pub temperature: Option<TemperatureConfig>,
+    /// Configuration for the TCP reachability section.
+    pub reachability: Option<ReachabilityConfig>,
     /// Configuration for the services section.
     /// Map from the service name (without a suffix of .service) to the configuration for the service.
     pub services: Option<HashMap<String, ServiceConfig>>,
+    /// Configuration for the listening sockets section.
+    pub listening: Option<ListeningConfig>,
+    /// Configuration for the journal error summary section.
+    pub journal: Option<JournalConfig>,
     /// Configuration for the last login section.
     pub last_login: Option<LastLoginConfig>,
+    /// Custom commands, each shown as its own section.
+    pub custom: Option<Vec<CustomCommandConfig>>,
+    /// Configuration for how times and durations are displayed.
+    /// Applies to the uptime in the general section and login times in the last login section.
+    #[serde(default)]
+    pub time_format: TimeFormatConfig,
+    /// Configuration for the units temperatures, sizes and frequencies are displayed in.
+    #[serde(default)]
+    pub units: UnitsConfig,
 }
 
 /// Configuration for the general section.
@@ -30,9 +66,57 @@ pub struct GeneralInfoConfig {
     /// Comparison value will be the used memory divided by the total memory for a scale from 0-1 e.g. 4GB used on a 16GB machine generates a comparison value of 0.25.
     /// This allows configurations to be machine independent while also allowing sensible colouring.
     pub memory: ConditionalColour<f32>,
+    /// Optionally show swap usage, conditionally colouring the used swap value.
+    /// Comparison value will be the used swap divided by the total swap for a scale from 0-1.
+    pub swap: Option<ConditionalColour<f32>>,
+    /// Show a breakdown of buffers/cache, shared memory and huge pages read from `/proc/meminfo`.
+    #[serde(default)]
+    pub memory_breakdown: bool,
     /// Conditionally colour the current CPU frequency.
-    /// Comparison value is the CPU frequency in MHz.
-    pub cpu_frequency: ConditionalColour<u64>,
+    /// Comparison value is the CPU frequency in the configured frequency unit, MHz by default.
+    pub cpu_frequency: ConditionalColour<f32>,
+    /// Optionally show the overall CPU usage and the usage and frequency of each core.
+    pub cpu_cores: Option<CpuCoresConfig>,
+}
+
+/// Configuration for the per core CPU view in the general section.
+#[derive(Debug, Deserialize)]
+pub struct CpuCoresConfig {
+    /// Conditionally colour the CPU usage.
+    /// Comparison value is the usage as a percentage from 0-100.
+    pub usage: ConditionalColour<f32>,
+    /// Time in milliseconds between the two samples used to calculate CPU usage.
+    #[serde(default = "default_sample_interval_ms")]
+    pub sample_interval_ms: u64,
+    /// Width of the usage bar of each core.
+    #[serde(default = "default_core_bar_width")]
+    pub bar_width: usize,
+    /// Number of cores shown on each line.
+    #[serde(default = "default_core_columns")]
+    pub columns: usize,
+}
+
+fn default_sample_interval_ms() -> u64 {
+    200
+}
+
+fn default_core_bar_width() -> usize {
+    10
+}
+
+fn default_core_columns() -> usize {
+    2
+}
+
+/// Configuration for the pressure stall information section.
+#[derive(Debug, Deserialize)]
+pub struct PressureConfig {
+    /// Conditionally colour the share of time in which at least some tasks were stalled on a resource.
+    /// Comparison value is the percentage from 0-100 as reported in `/proc/pressure/*`.
+    pub some: ConditionalColour<f32>,
+    /// Conditionally colour the share of time in which all non-idle tasks were stalled on a resource.
+    /// Comparison value is the percentage from 0-100 as reported in `/proc/pressure/*`.
+    pub full: ConditionalColour<f32>,
 }
 
 /// Configuration for the storage section.
@@ -41,12 +125,370 @@ pub struct StorageConfig {
     /// Conditionally colour the usage bar of each disk.
     /// Comparison value a value from 0-1 calculated from used divided by total storage space.
     pub usage_colouring: ConditionalColour<f32>,
+    /// Optionally show the inode usage of each disk, conditionally colouring the inode usage percentage.
+    /// Comparison value a value from 0-1 calculated from used divided by total inodes.
+    pub inode_colouring: Option<ConditionalColour<f32>>,
+    /// Show a second bar for the inode usage of each disk. Requires `inode_colouring` to be set.
+    #[serde(default)]
+    pub inode_bar: bool,
     /// Vector of path prefixes to exclude. Any disk mounts matching this path will be ignored.
     /// Use cases include docker volumes which appear in `/var/lib/docker` and often are the same as the disk they are located on.
     ///
     /// Currently uses the `.starts_with(prefix)` function to match so all directories should be terminated with a path separator.
     /// Otherwise `/example` will exclude both `/example/` and `/example2/` while `/example/` will only match the first.
     pub exclude_prefixes: Vec<String>,
+    /// Time in milliseconds to wait for each mount to respond before it is shown as stale e.g. an unreachable NFS share.
+    /// Mounts are read in parallel so this is also the longest the section can be delayed by unresponsive mounts.
+    /// Defaults to 1000.
+    #[serde(default = "default_stat_timeout_ms")]
+    pub stat_timeout_ms: u64,
+    /// Optionally only include mounts matching at least one of the rules.
+    pub include: Option<MountFilterConfig>,
+    /// Exclude mounts matching any of the rules, in addition to the built-in exclusions of `default_exclude`.
+    #[serde(default)]
+    pub exclude: MountFilterConfig,
+    /// Exclude pseudo filesystems such as `tmpfs` and the mounts under `/sys`, `/proc` and `/run` other than `/run/media`.
+    /// Disable to show these mounts, e.g. to include `tmpfs` mounts with `include`. Defaults to true.
+    #[serde(default = "default_storage_default_exclude")]
+    pub default_exclude: bool,
+    /// Mapping of mount points to a label to display instead of the mount point.
+    #[serde(default)]
+    pub labels: HashMap<String, String>,
+    /// Only show the mount points which have a label in `labels`.
+    #[serde(default)]
+    pub only_labelled: bool,
+    /// Collapse mounts of the same filesystem into a single entry listing all of their mount points.
+    /// This covers bind mounts, btrfs subvolumes and overlay mounts sharing the same underlying filesystem.
+    #[serde(default)]
+    pub deduplicate: bool,
+    /// Group entries under the physical disk they are stored on.
+    #[serde(default)]
+    pub group_by_disk: bool,
+    /// Optionally sort the entries. Defaults to the order reported by the system.
+    pub sort_by: Option<StorageSort>,
+    /// Mapping of mount points to configuration overriding the section configuration for that mount.
+    #[serde(default)]
+    pub mounts: HashMap<String, MountConfig>,
+    /// Optionally record the usage of each mount over time and estimate when each mount will be full.
+    pub history: Option<StorageHistoryConfig>,
+}
+
+/// Configuration for recording storage usage history and estimating when mounts will be full.
+#[derive(Debug, Deserialize)]
+pub struct StorageHistoryConfig {
+    /// Conditionally colour the estimated time until each mount is full.
+    /// Comparison value is the estimated number of days remaining.
+    pub days_remaining_colouring: ConditionalColourBelow<f64>,
+    /// Path of the file used to store the usage history.
+    /// Default is $XDG_STATE_HOME/sysstatus/storage_history.json or $HOME/.local/state/sysstatus/storage_history.json
+    pub path: Option<PathBuf>,
+    /// Minimum time in minutes between recorded samples of each mount.
+    #[serde(default = "default_min_sample_interval_minutes")]
+    pub min_sample_interval_minutes: i64,
+    /// Samples older than this number of days are discarded.
+    #[serde(default = "default_max_age_days")]
+    pub max_age_days: i64,
+}
+
+fn default_stat_timeout_ms() -> u64 {
+    1000
+}
+
+fn default_storage_default_exclude() -> bool {
+    true
+}
+
+fn default_min_sample_interval_minutes() -> i64 {
+    60
+}
+
+fn default_max_age_days() -> i64 {
+    30
+}
+
+/// Order of the entries in the storage section.
+#[derive(Debug, Deserialize)]
+pub enum StorageSort {
+    /// Alphabetically by mount point.
+    Mount,
+    /// Highest used percentage first.
+    UsedPercent,
+    /// Least free space first.
+    Free,
+}
+
+/// Configuration for a single mount point in the storage section.
+#[derive(Debug, Deserialize)]
+pub struct MountConfig {
+    /// Optionally override the `usage_colouring` of the storage section for this mount.
+    pub usage_colouring: Option<ConditionalColour<f32>>,
+    /// Optionally colour the usage bar by the free space remaining instead of the used ratio.
+    /// Comparison value is the free space in bytes. Takes precedence over `usage_colouring`.
+    pub free_colouring: Option<ConditionalColourBelow<u64>>,
+}
+
+/// Rules used to match mounts in the storage section. A mount matches if it matches any of the rules.
+#[derive(Debug, Default, Deserialize)]
+pub struct MountFilterConfig {
+    /// Filesystem types e.g. `tmpfs`, `squashfs` or `overlay`.
+    #[serde(default)]
+    pub fs_types: Vec<String>,
+    /// Glob patterns matched against the device name e.g. `/dev/loop*`.
+    #[serde(default, with = "patterns")]
+    pub devices: Vec<Pattern>,
+    /// Glob patterns matched against the mount point e.g. `/snap/**`.
+    /// `*` does not match a path separator while `**` matches any number of directories.
+    #[serde(default, with = "patterns")]
+    pub mount_globs: Vec<Pattern>,
+    /// Regular expressions matched against the mount point e.g. `^/var/lib/(docker|kubelet)/`.
+    #[serde(default, with = "regexes")]
+    pub mount_regexes: Vec<Regex>,
+}
+
+/// Configuration for the disk I/O section.
+#[derive(Debug, Deserialize)]
+pub struct DiskIoConfig {
+    /// Conditionally colour the utilisation of each block device.
+    /// Comparison value is the percentage from 0-100 of the sample interval during which the device was busy.
+    pub utilisation: ConditionalColour<f32>,
+    /// Time in milliseconds between the two samples of `/proc/diskstats` used to calculate rates.
+    #[serde(default = "default_disk_io_sample_interval_ms")]
+    pub sample_interval_ms: u64,
+    /// Vector of device name prefixes to exclude e.g. `loop` or `ram`.
+    /// Partitions are always excluded so only whole block devices are shown.
+    #[serde(default)]
+    pub exclude_prefixes: Vec<String>,
+}
+
+fn default_disk_io_sample_interval_ms() -> u64 {
+    500
+}
+
+/// Configuration for the SMART disk health section.
+///
+/// Requires `smartctl` from smartmontools, which usually needs to be run as root to read SMART data.
+#[derive(Debug, Deserialize)]
+pub struct DiskHealthConfig {
+    /// Block devices to check e.g. `/dev/sda` or `/dev/nvme0`.
+    pub devices: Vec<String>,
+    /// Conditionally colour the reallocated and pending sector counts.
+    /// Comparison value is the number of sectors. For NVMe drives the pending count is the number of media errors.
+    pub sectors: ConditionalColour<u64>,
+    /// Conditionally colour the percentage of the rated endurance of an SSD which has been used.
+    /// Comparison value is the percentage from 0-100.
+    pub wear: ConditionalColour<u64>,
+    /// Conditionally colour the drive temperature.
+    /// Comparison value is the temperature in the configured unit, °C by default.
+    pub temperature: ConditionalColour<u64>,
+    /// Optionally colour the power on time.
+    /// Comparison value is the number of hours the drive has been powered on.
+    pub power_on_hours: Option<ConditionalColour<u64>>,
+    /// Time in milliseconds to wait for smartctl to read each device before it is killed and an error shown.
+    /// Devices are read in parallel so this is also the longest the section can be delayed.
+    /// Defaults to 10000 to allow sleeping drives to spin up.
+    #[serde(default = "default_disk_health_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+fn default_disk_health_timeout_ms() -> u64 {
+    10000
+}
+
+/// Configuration for the file freshness section.
+#[derive(Debug, Deserialize)]
+pub struct FilesConfig {
+    /// Files to check, shown in the order given.
+    pub checks: Vec<FileCheckConfig>,
+    /// Time in milliseconds to wait for each check before it is shown as stale e.g. a backup on an unreachable NAS.
+    /// Checks run in parallel so this is also the longest the section can be delayed by unresponsive filesystems.
+    /// Defaults to 2000.
+    #[serde(default = "default_files_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+fn default_files_timeout_ms() -> u64 {
+    2000
+}
+
+/// Expected properties of a file, or of the newest file matching a glob pattern or inside a directory.
+#[derive(Debug, Deserialize)]
+pub struct FileCheckConfig {
+    /// Path of a file or directory, or a glob pattern e.g. `/backups/*.tar.gz`.
+    /// Directories are replaced by the files directly inside them.
+    pub path: String,
+    /// Optionally display this label instead of the path.
+    pub label: Option<String>,
+    /// Whether the path is expected to exist. Set to false for files such as stale lock files. Defaults to true.
+    #[serde(default = "default_file_exists")]
+    pub exists: bool,
+    /// Maximum age in seconds of the newest file.
+    pub max_age: Option<u64>,
+    /// Minimum size in bytes of the newest file.
+    pub min_size: Option<u64>,
+    /// Maximum size in bytes of the newest file.
+    pub max_size: Option<u64>,
+    /// Optionally colour the age of the newest file. Otherwise the age is coloured red when it exceeds `max_age`.
+    /// Comparison value is the age in seconds.
+    pub age: Option<ConditionalColour<u64>>,
+}
+
+fn default_file_exists() -> bool {
+    true
+}
+
+/// Configuration for the array section.
+///
+/// Linux software RAID arrays from `/proc/mdstat` are always shown.
+/// Degraded, failed or inactive arrays are coloured red.
+#[derive(Debug, Deserialize)]
+pub struct RaidConfig {
+    /// Show the health of ZFS pools using `zpool list`.
+    #[serde(default)]
+    pub zfs: bool,
+    /// Btrfs mount points to show device error counters for using `btrfs device stats`, which usually needs to be run as root.
+    #[serde(default)]
+    pub btrfs_mounts: Vec<String>,
+    /// Time in milliseconds to wait for each `zpool` or `btrfs` command before it is killed and an error shown.
+    /// `zpool` blocks indefinitely on a suspended pool. Defaults to 5000.
+    #[serde(default = "default_raid_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+fn default_raid_timeout_ms() -> u64 {
+    5000
+}
+
+/// Configuration for the battery and power supply section.
+///
+/// Power supplies are read from `/sys/class/power_supply`.
+#[derive(Debug, Deserialize)]
+pub struct PowerConfig {
+    /// Conditionally colour the charge of batteries.
+    /// Comparison value is the charge as a percentage from 0-100.
+    pub charge: ConditionalColourBelow<u64>,
+    /// Conditionally colour the health of batteries.
+    /// Comparison value is the full capacity as a percentage of the design capacity.
+    pub health: ConditionalColourBelow<u64>,
+}
+
+/// Configuration for the UPS section.
+///
+/// UPS status is read from a Network UPS Tools `upsd`.
+#[derive(Debug, Deserialize)]
+pub struct UpsConfig {
+    /// Address of `upsd`. Defaults to `localhost:3493`.
+    #[serde(default = "default_ups_address")]
+    pub address: String,
+    /// Names of the UPSes to show. Defaults to all UPSes known to `upsd`.
+    #[serde(default)]
+    pub names: Vec<String>,
+    /// Time in milliseconds to wait for `upsd` to connect or respond. Defaults to 1000.
+    #[serde(default = "default_ups_timeout_ms")]
+    pub timeout_ms: u64,
+    /// Conditionally colour the battery charge.
+    /// Comparison value is the charge as a percentage from 0-100.
+    pub charge: ConditionalColourBelow<u64>,
+    /// Conditionally colour the battery runtime.
+    /// Comparison value is the estimated runtime in seconds.
+    pub runtime: ConditionalColourBelow<u64>,
+    /// Conditionally colour the load.
+    /// Comparison value is the load as a percentage of the rated power.
+    pub load: ConditionalColour<u64>,
+}
+
+fn default_ups_address() -> String {
+    "localhost:3493".to_string()
+}
+
+fn default_ups_timeout_ms() -> u64 {
+    1000
+}
+
+/// Configuration for the Raspberry Pi health section.
+///
+/// Shows whether under-voltage, frequency capping, throttling or the soft temperature limit are active
+/// or have occurred since boot, as reported by the firmware.
+#[derive(Debug, Deserialize)]
+pub struct PiHealthConfig {
+    /// Show the memory split between the ARM CPU and the GPU using `vcgencmd`.
+    #[serde(default)]
+    pub memory_split: bool,
+}
+
+/// Configuration for the TCP reachability section.
+#[derive(Debug, Deserialize)]
+pub struct ReachabilityConfig {
+    /// Endpoints to connect to, checked in parallel and shown in the order given.
+    pub targets: Vec<ReachabilityTargetConfig>,
+    /// Conditionally colour the latency of reachable targets.
+    /// Comparison value is the time taken to connect in milliseconds.
+    pub latency: ConditionalColour<f64>,
+}
+
+/// A TCP endpoint checked by the reachability section.
+#[derive(Debug, Deserialize)]
+pub struct ReachabilityTargetConfig {
+    /// Optionally display this name instead of the address.
+    pub name: Option<String>,
+    /// Address to connect to as `host:port`, or just a port to check a port listening on this host.
+    pub address: String,
+    /// Time in milliseconds to wait for the connection, including resolving the host, before the target is shown as down.
+    /// Defaults to 1000.
+    #[serde(default = "default_reachability_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+fn default_reachability_timeout_ms() -> u64 {
+    1000
+}
+
+/// Configuration for the temperature section.
+///
+/// Sensors are matched by the label from their driver e.g. `Package id 0` or `Composite`, which does not include the
+/// name of the chip so is shared by identical sensors on different chips e.g. the `Composite` sensor of each NVMe drive.
+#[derive(Debug, Deserialize)]
+pub struct TemperatureConfig {
+    /// Conditionally colour the temperatures presented.
+    /// Comparison value is the temperature in the configured unit, °C by default.
+    #[serde(flatten)]
+    pub colouring: ConditionalColour<f32>,
+    /// Only show sensors with a label matching any of these glob patterns. All sensors are shown if empty.
+    #[serde(default, with = "patterns")]
+    pub include: Vec<Pattern>,
+    /// Hide sensors with a label matching any of these glob patterns.
+    #[serde(default, with = "patterns")]
+    pub exclude: Vec<Pattern>,
+    /// Map from a sensor label to the name shown instead e.g. `Package id 0` to `CPU`.
+    #[serde(default)]
+    pub aliases: HashMap<String, String>,
+    /// Groups of sensors shown as a single line with the temperature of the hottest sensor of the group.
+    /// Each sensor is placed in the first group it matches.
+    #[serde(default)]
+    pub groups: Vec<TemperatureGroupConfig>,
+    /// Optionally colour temperatures relative to the critical temperature reported by each sensor.
+    /// Comparison value is the temperature minus the critical temperature e.g. -15 when 15°C below critical.
+    /// Sensors which do not report a critical temperature use `max_colouring` or `colouring` instead.
+    pub critical_colouring: Option<ConditionalColour<f32>>,
+    /// Optionally colour temperatures relative to the maximum temperature reported by each sensor.
+    /// Comparison value is the temperature minus the maximum temperature.
+    /// Sensors which do not report a maximum temperature use `colouring` instead.
+    pub max_colouring: Option<ConditionalColour<f32>>,
+    /// Map from the name shown for a sensor or group to the colouring used instead of any of the above.
+    #[serde(default)]
+    pub sensors: HashMap<String, ConditionalColour<f32>>,
+    /// Show the critical temperature reported by each sensor after its temperature.
+    #[serde(default)]
+    pub show_critical: bool,
+}
+
+/// Configuration for a group of temperature sensors.
+#[derive(Debug, Deserialize)]
+pub struct TemperatureGroupConfig {
+    /// Name shown for the group.
+    pub name: String,
+    /// Glob patterns matching the labels of the sensors in the group e.g. `Core *`.
+    #[serde(with = "patterns")]
+    pub sensors: Vec<Pattern>,
 }
 
 /// Configuration for a systemd service.
@@ -57,6 +499,97 @@ pub struct ServiceConfig {
     pub memory_usage: Option<ConditionalColour<u64>>,
 }
 
+/// Configuration for the listening sockets section.
+#[derive(Debug, Deserialize)]
+pub struct ListeningConfig {
+    /// Ports which are expected to be listening. If not empty, these ports are coloured green and all other ports red.
+    #[serde(default)]
+    pub allowed_ports: Vec<u16>,
+    /// Hide sockets only listening on a loopback address e.g. `127.0.0.1` or `::1`.
+    #[serde(default)]
+    pub hide_loopback: bool,
+}
+
+/// Configuration for the kernel events section.
+///
+/// Counts OOM kills, segfaults, hung tasks and I/O errors in the kernel ring buffer,
+/// read from `/dev/kmsg` or `dmesg` which may require root if `kernel.dmesg_restrict` is set.
+#[derive(Debug, Deserialize)]
+pub struct KernelConfig {
+    /// Optionally only count events in this many of the most recent minutes. Defaults to counting events since boot.
+    pub window_minutes: Option<u64>,
+    /// Conditionally colour the number of each event.
+    pub count: ConditionalColour<u64>,
+}
+
+/// Configuration for the journal error summary section.
+///
+/// Shows the number of journal entries at priority err or above per unit.
+#[derive(Debug, Deserialize)]
+pub struct JournalConfig {
+    /// Only include entries since this time. Accepts any value which `journalctl --since` accepts e.g. `-24h`, `today`.
+    /// Defaults to `-24h`.
+    #[serde(default = "default_journal_since")]
+    pub since: String,
+    /// Conditionally colour the number of errors of each unit.
+    pub count: ConditionalColour<u64>,
+    /// Optionally limit the number of units shown, keeping the units with the most errors.
+    pub max_units: Option<usize>,
+    /// Maximum number of characters of the latest message shown. Defaults to 60.
+    #[serde(default = "default_journal_message_width")]
+    pub message_width: usize,
+    /// Time in milliseconds to wait for `journalctl` before it is killed and an error shown. Defaults to 5000.
+    #[serde(default = "default_journal_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+fn default_journal_since() -> String {
+    "-24h".to_string()
+}
+
+fn default_journal_message_width() -> usize {
+    60
+}
+
+fn default_journal_timeout_ms() -> u64 {
+    5000
+}
+
+/// Configuration for a custom command section.
+#[derive(Debug, Deserialize)]
+pub struct CustomCommandConfig {
+    /// Name shown as the title of the section.
+    pub name: String,
+    /// Command run with `sh -c`. If it exits unsuccessfully its stdout is shown verbatim in red along with the exit status.
+    pub command: String,
+    /// Time in milliseconds to wait for the command before it is killed. Defaults to 5000.
+    #[serde(default = "default_custom_timeout_ms")]
+    pub timeout_ms: u64,
+    /// How the output of the command is shown. Defaults to `Verbatim`.
+    #[serde(default)]
+    pub format: CustomOutputFormat,
+    /// Map from the name of a row to the colouring of its value.
+    /// Comparison value is the value of the row, which is only coloured if it is a number.
+    #[serde(default)]
+    pub thresholds: HashMap<String, ConditionalColour<f64>>,
+}
+
+fn default_custom_timeout_ms() -> u64 {
+    5000
+}
+
+/// How the output of a custom command is shown.
+#[derive(Debug, Default, Deserialize)]
+pub enum CustomOutputFormat {
+    /// Each line of the output as is.
+    #[default]
+    Verbatim,
+    /// Rows parsed from lines of the form `name=value`.
+    KeyValue,
+    /// Rows parsed from a JSON object. Keys of nested objects are joined with `.` e.g. `replication.lag`.
+    Json,
+}
+
 /// Configuration for the last login section.
 #[derive(Debug, Deserialize)]
 pub struct LastLoginConfig {
@@ -80,6 +613,80 @@ pub struct UserLastLoginConfig {
     pub max_lines: Option<usize>,
 }
 
+/// Configuration for how times and durations are displayed.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct TimeFormatConfig {
+    /// Whether times are shown relative to now e.g. `2h ago` or as absolute times.
+    pub style: TimeStyle,
+    /// strftime style format string used for absolute times.
+    ///
+    /// See [chrono's documentation](https://docs.rs/chrono/0.4/chrono/format/strftime/index.html) for the accepted specifiers.
+    pub format: String,
+    /// Time zone used for absolute times e.g. `Europe/London`. Defaults to the local time zone.
+    pub timezone: Option<Tz>,
+    /// Maximum number of units shown in durations and relative times e.g. 2 shows `3d 4h` rather than `3d 4h 12m`.
+    pub max_units: usize,
+}
+
+impl Default for TimeFormatConfig {
+    fn default() -> Self {
+        TimeFormatConfig {
+            style: TimeStyle::Relative,
+            format: "%Y-%m-%d %H:%M".to_string(),
+            timezone: None,
+            max_units: 2,
+        }
+    }
+}
+
+/// How times are displayed.
+#[derive(Debug, Deserialize)]
+pub enum TimeStyle {
+    /// Relative to the current time e.g. `2h ago`.
+    Relative,
+    /// Absolute time using the configured format and time zone.
+    Absolute,
+}
+
+/// Configuration for the units values are displayed in.
+///
+/// Temperature and frequency thresholds are compared to the value in the configured unit.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct UnitsConfig {
+    /// Unit of temperatures in the temperature and disk health sections.
+    pub temperature: TemperatureUnit,
+    /// Unit prefixes of sizes in the general, storage, disk I/O, services and Pi health sections.
+    pub bytes: ByteUnit,
+    /// Unit of CPU frequencies in the general section.
+    pub frequency: FrequencyUnit,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub enum TemperatureUnit {
+    #[default]
+    Celsius,
+    Fahrenheit,
+    Kelvin,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub enum ByteUnit {
+    /// Powers of 1000 e.g. `MB` and `GB`.
+    #[default]
+    Si,
+    /// Powers of 1024 e.g. `MiB` and `GiB`.
+    Iec,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub enum FrequencyUnit {
+    #[default]
+    MHz,
+    GHz,
+}
+
 /// Select a colour by comparing the comparison value to the minimum value for each colouring level in order
 /// and selecting the last colour passing the comparison.
 ///
@@ -129,6 +736,49 @@ pub struct ColouringLevel<T: PartialOrd> {
     pub colour: Color,
 }
 
+/// Select a colour by comparing the comparison value to the maximum value for each colouring level in order
+/// and selecting the last colour for which the comparison value is below `max`.
+/// This is the counterpart of `ConditionalColour` for values where lower is worse, such as free space.
+///
+/// # Examples
+/// ```
+/// let cc = ConditionalColourBelow {
+///     default_colour: Color::Green,
+///     levels: vec![
+///         ColouringLevelBelow {
+///             max: 20,
+///             colour: Color::Yellow,
+///         },
+///         ColouringLevelBelow {
+///             max: 5,
+///             colour: Color::Red,
+///         },
+///     ],
+/// };
+/// assert_eq!(util::select_colour_below(20, &cc), Color::Green);
+/// assert_eq!(util::select_colour_below(19, &cc), Color::Yellow);
+/// assert_eq!(util::select_colour_below(4, &cc), Color::Red);
+/// ```
+#[derive(Debug, Deserialize)]
+pub struct ConditionalColourBelow<T: PartialOrd> {
+    /// The default colour to use if the comparison value is not below the maximum of the first level.
+    #[serde(with = "LocalColor")]
+    pub default_colour: Color,
+    /// An list of colouring levels to check in order
+    pub levels: Vec<ColouringLevelBelow<T>>,
+}
+
+/// Struct used as part of the `ConditionalColourBelow` struct to decide which `colour` to use when the comparison
+/// value is below `max`.
+#[derive(Debug, Deserialize)]
+pub struct ColouringLevelBelow<T: PartialOrd> {
+    /// The value the comparison value should be below before this colour is used.
+    pub max: T,
+    /// The colour to use.
+    #[serde(with = "LocalColor")]
+    pub colour: Color,
+}
+
 // Serde calls this the definition of the remote type. It is just a copy of the
 // remote data structure. The `remote` attribute gives the path to the actual
 // type we intend to derive code for.
@@ -173,3 +823,37 @@ mod opt_color {
         Ok(helper.map(|Helper(external)| external))
     }
 }
+
+/// Module handling serde deserialization of a list of glob patterns.
+mod patterns {
+    use glob::Pattern;
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer};
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<Pattern>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Vec::<String>::deserialize(deserializer)?
+            .iter()
+            .map(|pattern| Pattern::new(pattern).map_err(D::Error::custom))
+            .collect()
+    }
+}
+
+/// Module handling serde deserialization of a list of regular expressions.
+mod regexes {
+    use regex::Regex;
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer};
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<Regex>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Vec::<String>::deserialize(deserializer)?
+            .iter()
+            .map(|regex| Regex::new(regex).map_err(D::Error::custom))
+            .collect()
+    }
+}
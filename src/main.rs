@@ -6,8 +6,11 @@ use structopt::StructOpt;
 use sysinfo::{System, SystemExt};
 use thiserror::Error;
 
+mod command;
 mod components;
 mod config;
+mod time_format;
+mod units;
 mod util;
 
 use config::SysStatusConfig;
@@ -92,27 +95,92 @@ fn main() {
                     if let Some(general_info) = &cfg.general_info {
                         println!(
                             "{}",
-                            components::general_info::general_info(&mut sys, general_info)
+                            components::general_info::general_info(
+                                &mut sys,
+                                general_info,
+                                &cfg.time_format,
+                                &cfg.units
+                            )
                         );
                     }
 
+                    if let Some(pressure) = &cfg.pressure {
+                        components::pressure::print_pressure(pressure, indent);
+                    }
+
+                    if let Some(kernel) = &cfg.kernel {
+                        components::kernel::print_kernel(kernel, &cfg.time_format, indent);
+                    }
+
                     if let Some(storage) = &cfg.storage {
-                        components::disks::print_disks(&mut sys, storage, indent);
+                        components::disks::print_disks(storage, &cfg.units, indent);
+                    }
+
+                    if let Some(files) = &cfg.files {
+                        components::files::print_files(files, &cfg.time_format, &cfg.units, indent);
+                    }
+
+                    if let Some(disk_io) = &cfg.disk_io {
+                        components::disk_io::print_disk_io(disk_io, &cfg.units, indent);
+                    }
+
+                    if let Some(disk_health) = &cfg.disk_health {
+                        components::disk_health::print_disk_health(disk_health, &cfg.units, indent);
+                    }
+
+                    if let Some(raid) = &cfg.raid {
+                        components::raid::print_raid(raid, indent);
+                    }
+
+                    if let Some(power) = &cfg.power {
+                        components::power::print_power(power, &cfg.time_format, indent);
+                    }
+
+                    if let Some(ups) = &cfg.ups {
+                        components::ups::print_ups(ups, &cfg.time_format, indent);
+                    }
+
+                    if let Some(pi_health) = &cfg.pi_health {
+                        components::pi_health::print_pi_health(pi_health, &cfg.units, indent);
                     }
 
                     if let Some(temperature) = &cfg.temperature {
                         println!(
                             "Temperatures:\n{}\n",
-                            components::temperature::temperature(&mut sys, temperature)
+                            components::temperature::temperature(
+                                &mut sys,
+                                temperature,
+                                &cfg.units.temperature,
+                            )
                         );
                     }
 
+                    if let Some(reachability) = &cfg.reachability {
+                        components::reachability::print_reachability(reachability, indent);
+                    }
+
                     if let Some(services) = &cfg.services {
-                        components::services::print_services(services, indent);
+                        components::services::print_services(services, &cfg.units, indent);
+                    }
+
+                    if let Some(journal) = &cfg.journal {
+                        components::journal::print_journal(journal, &cfg.time_format, indent);
+                    }
+
+                    if let Some(listening) = &cfg.listening {
+                        components::listening::print_listening(listening, indent);
+                    }
+
+                    if let Some(custom) = &cfg.custom {
+                        components::custom::print_custom(custom, indent);
                     }
 
                     if let Some(last_login) = &cfg.last_login {
-                        components::last_login::print_last_login(last_login, indent);
+                        components::last_login::print_last_login(
+                            last_login,
+                            &cfg.time_format,
+                            indent,
+                        );
                     }
                 }
                 Err(e) => eprintln!("Config error: {}", e),
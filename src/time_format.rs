@@ -0,0 +1,127 @@
+use crate::config::{TimeFormatConfig, TimeStyle};
+use chrono::{DateTime, Local, TimeZone, Utc};
+
+const UNITS: [(&str, u64); 5] = [
+    ("w", 7 * 24 * 60 * 60),
+    ("d", 24 * 60 * 60),
+    ("h", 60 * 60),
+    ("m", 60),
+    ("s", 1),
+];
+
+/// Format a duration in seconds as a compact string such as `3d 4h`.
+///
+/// Only `max_units` units are considered, starting from the largest non-zero unit.
+/// Units with a value of zero within that range are omitted e.g. `1w 0d 4h` with 3 units is shown as `1w 4h`.
+pub fn format_duration(seconds: u64, max_units: usize) -> String {
+    let first_unit = UNITS
+        .iter()
+        .position(|(_, unit_seconds)| seconds >= *unit_seconds)
+        .unwrap_or(UNITS.len() - 1);
+
+    let mut remaining = seconds;
+    let parts = UNITS[first_unit..]
+        .iter()
+        .take(max_units.max(1))
+        .filter_map(|(suffix, unit_seconds)| {
+            let value = remaining / unit_seconds;
+            remaining %= unit_seconds;
+            match value {
+                0 => None,
+                _ => Some(format!("{}{}", value, suffix)),
+            }
+        })
+        .collect::<Vec<String>>();
+
+    match parts.is_empty() {
+        true => format!("0{}", UNITS[first_unit].0),
+        false => parts.join(" "),
+    }
+}
+
+/// Format a time relative to `now` e.g. `2h ago` or `in 5m`.
+pub fn format_relative(time: &DateTime<Utc>, now: &DateTime<Utc>, max_units: usize) -> String {
+    let seconds = now.signed_duration_since(*time).num_seconds();
+    match seconds {
+        0 => "just now".to_string(),
+        s if s > 0 => format!("{} ago", format_duration(s as u64, max_units)),
+        s => format!("in {}", format_duration(s.unsigned_abs(), max_units)),
+    }
+}
+
+/// Format a time using the configured strftime format, in the configured time zone or the local time zone if unset.
+pub fn format_absolute(time: &DateTime<Utc>, cfg: &TimeFormatConfig) -> String {
+    match cfg.timezone {
+        Some(tz) => tz.from_utc_datetime(&time.naive_utc()).format(&cfg.format),
+        None => Local
+            .from_utc_datetime(&time.naive_utc())
+            .format(&cfg.format),
+    }
+    .to_string()
+}
+
+/// Format a time according to the configured style.
+pub fn format_time(time: &DateTime<Utc>, now: &DateTime<Utc>, cfg: &TimeFormatConfig) -> String {
+    match cfg.style {
+        TimeStyle::Relative => format_relative(time, now, cfg.max_units),
+        TimeStyle::Absolute => format_absolute(time, cfg),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::config::{TimeFormatConfig, TimeStyle};
+    use crate::time_format;
+    use chrono::{TimeZone, Utc};
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(time_format::format_duration(0, 2), "0s");
+        assert_eq!(time_format::format_duration(59, 2), "59s");
+        assert_eq!(time_format::format_duration(3 * 3600 + 30, 2), "3h");
+        assert_eq!(time_format::format_duration(3 * 3600 + 30, 3), "3h 30s");
+        assert_eq!(
+            time_format::format_duration(3 * 86400 + 4 * 3600 + 5, 2),
+            "3d 4h"
+        );
+        assert_eq!(
+            time_format::format_duration(7 * 86400 + 4 * 3600, 3),
+            "1w 4h"
+        );
+        assert_eq!(time_format::format_duration(7 * 86400 + 4 * 3600, 2), "1w");
+        assert_eq!(time_format::format_duration(90, 0), "1m");
+    }
+
+    #[test]
+    fn test_format_relative() {
+        let now = Utc.with_ymd_and_hms(2022, 5, 1, 12, 0, 0).unwrap();
+        let earlier = Utc.with_ymd_and_hms(2022, 5, 1, 9, 45, 0).unwrap();
+        let later = Utc.with_ymd_and_hms(2022, 5, 1, 12, 5, 0).unwrap();
+        assert_eq!(time_format::format_relative(&now, &now, 2), "just now");
+        assert_eq!(
+            time_format::format_relative(&earlier, &now, 2),
+            "2h 15m ago"
+        );
+        assert_eq!(time_format::format_relative(&earlier, &now, 1), "2h ago");
+        assert_eq!(time_format::format_relative(&later, &now, 2), "in 5m");
+    }
+
+    #[test]
+    fn test_format_absolute() {
+        let time = Utc.with_ymd_and_hms(2022, 5, 1, 12, 0, 0).unwrap();
+        let cfg = TimeFormatConfig {
+            style: TimeStyle::Absolute,
+            format: "%Y-%m-%d %H:%M %Z".to_string(),
+            timezone: Some(chrono_tz::Europe::London),
+            max_units: 2,
+        };
+        assert_eq!(
+            time_format::format_absolute(&time, &cfg),
+            "2022-05-01 13:00 BST"
+        );
+        assert_eq!(
+            time_format::format_time(&time, &time, &cfg),
+            "2022-05-01 13:00 BST"
+        );
+    }
+}
@@ -0,0 +1,110 @@
+use crate::config::{ByteUnit, FrequencyUnit, TemperatureUnit};
+use bytesize::ByteSize;
+
+/// Format a size in bytes e.g. `1.5 GB` or `1.4 GiB`.
+pub fn format_bytes(bytes: u64, unit: &ByteUnit) -> String {
+    match unit {
+        ByteUnit::Si => ByteSize::b(bytes).to_string_as(false),
+        // bytesize calls the IEC binary prefixes "SI" prefixes and writes kibibytes as `kiB`.
+        ByteUnit::Iec => ByteSize::b(bytes).to_string_as(true).replace("kiB", "KiB"),
+    }
+}
+
+/// Convert a temperature in °C to the configured unit.
+pub fn convert_temperature(celsius: f32, unit: &TemperatureUnit) -> f32 {
+    match unit {
+        TemperatureUnit::Celsius => celsius,
+        TemperatureUnit::Fahrenheit => celsius * 9.0 / 5.0 + 32.0,
+        TemperatureUnit::Kelvin => celsius + 273.15,
+    }
+}
+
+pub fn temperature_symbol(unit: &TemperatureUnit) -> &'static str {
+    match unit {
+        TemperatureUnit::Celsius => "°C",
+        TemperatureUnit::Fahrenheit => "°F",
+        TemperatureUnit::Kelvin => "K",
+    }
+}
+
+/// Format a temperature in °C as a number in the configured unit without the unit symbol.
+/// Converted values are rounded to two decimal places to hide floating point noise.
+pub fn temperature_value(celsius: f32, unit: &TemperatureUnit) -> String {
+    ((convert_temperature(celsius, unit) * 100.0).round() / 100.0).to_string()
+}
+
+/// Format a temperature in °C in the configured unit e.g. `98.6°F`.
+pub fn format_temperature(celsius: f32, unit: &TemperatureUnit) -> String {
+    format!(
+        "{}{}",
+        temperature_value(celsius, unit),
+        temperature_symbol(unit)
+    )
+}
+
+/// Convert a frequency in MHz to the configured unit.
+pub fn convert_frequency(mhz: u64, unit: &FrequencyUnit) -> f32 {
+    match unit {
+        FrequencyUnit::MHz => mhz as f32,
+        FrequencyUnit::GHz => mhz as f32 / 1000.0,
+    }
+}
+
+pub fn frequency_symbol(unit: &FrequencyUnit) -> &'static str {
+    match unit {
+        FrequencyUnit::MHz => "MHz",
+        FrequencyUnit::GHz => "GHz",
+    }
+}
+
+/// Format a frequency in MHz as a number in the configured unit without the unit symbol.
+pub fn frequency_value(mhz: u64, unit: &FrequencyUnit) -> String {
+    match unit {
+        FrequencyUnit::MHz => mhz.to_string(),
+        FrequencyUnit::GHz => format!("{:.2}", convert_frequency(mhz, unit)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::config::{ByteUnit, FrequencyUnit, TemperatureUnit};
+    use crate::units;
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(units::format_bytes(512, &ByteUnit::Si), "512 B");
+        assert_eq!(units::format_bytes(1_500_000_000, &ByteUnit::Si), "1.5 GB");
+        assert_eq!(
+            units::format_bytes(1_500_000_000, &ByteUnit::Iec),
+            "1.4 GiB"
+        );
+        assert_eq!(units::format_bytes(2048, &ByteUnit::Iec), "2.0 KiB");
+    }
+
+    #[test]
+    fn test_temperature() {
+        assert_eq!(
+            units::format_temperature(38.85, &TemperatureUnit::Celsius),
+            "38.85°C"
+        );
+        assert_eq!(
+            units::format_temperature(37.0, &TemperatureUnit::Fahrenheit),
+            "98.6°F"
+        );
+        assert_eq!(
+            units::format_temperature(-40.0, &TemperatureUnit::Fahrenheit),
+            "-40°F"
+        );
+        assert_eq!(
+            units::format_temperature(25.0, &TemperatureUnit::Kelvin),
+            "298.15K"
+        );
+    }
+
+    #[test]
+    fn test_frequency() {
+        assert_eq!(units::frequency_value(2400, &FrequencyUnit::MHz), "2400");
+        assert_eq!(units::frequency_value(2400, &FrequencyUnit::GHz), "2.40");
+        assert_eq!(units::convert_frequency(2400, &FrequencyUnit::GHz), 2.4);
+    }
+}
@@ -1,4 +1,4 @@
-use crate::config::ConditionalColour;
+use crate::config::{ConditionalColour, ConditionalColourBelow};
 use colored::{Color, Colorize};
 use std::{cmp, iter};
 
@@ -13,6 +13,20 @@ pub fn select_colour_number<T: PartialOrd>(val: T, cond_colour: &ConditionalColo
     prev_colour
 }
 
+pub fn select_colour_below<T: PartialOrd>(
+    val: T,
+    cond_colour: &ConditionalColourBelow<T>,
+) -> Color {
+    let mut prev_colour = cond_colour.default_colour;
+    for level in &cond_colour.levels {
+        if val >= level.max {
+            break;
+        }
+        prev_colour = level.colour;
+    }
+    prev_colour
+}
+
 pub fn print_row<'a>(
     items_iter: impl IntoIterator<Item = &'a str>,
     column_sizes: impl IntoIterator<Item = &'a usize>,
@@ -23,10 +37,10 @@ pub fn print_row<'a>(
         prefix.unwrap_or(""),
         items_iter
             .into_iter()
-            .zip(column_sizes.into_iter())
+            .zip(column_sizes)
             .map(|(name, size)| format!("{: <size$}", name, size = size))
             .collect::<Vec<String>>()
-            .join(&"  ")
+            .join("  ")
     );
 }
 
@@ -36,7 +50,7 @@ pub fn format_width<'a>(
 ) -> Vec<String> {
     items_iter
         .into_iter()
-        .zip(column_sizes.into_iter())
+        .zip(column_sizes)
         .map(|(name, size)| format!("{: <size$}", name, size = size))
         .collect::<Vec<String>>()
 }
@@ -51,13 +65,13 @@ pub fn print_row_colour(
         prefix.unwrap_or(""),
         items_iter
             .into_iter()
-            .zip(colours_iter.into_iter())
+            .zip(colours_iter)
             .map(|(name, colour)| match colour {
                 Some(colour) => name.color(colour).to_string(),
                 None => name,
             })
             .collect::<Vec<String>>()
-            .join(&"  ")
+            .join("  ")
     );
 }
 
@@ -68,7 +82,7 @@ pub fn column_widths(header: &[&str], entries: impl Iterator<Item = Vec<usize>>)
         .fold(vec![0; header.len()], |acc, x: Vec<usize>| {
             x.iter()
                 .zip(acc.iter())
-                .map(|(a, b)| cmp::max(a, &b).to_owned())
+                .map(|(a, b)| cmp::max(a, b).to_owned())
                 .collect()
         })
 }
@@ -76,7 +90,9 @@ pub fn column_widths(header: &[&str], entries: impl Iterator<Item = Vec<usize>>)
 #[cfg(test)]
 mod test {
     use crate::config::ColouringLevel;
+    use crate::config::ColouringLevelBelow;
     use crate::config::ConditionalColour;
+    use crate::config::ConditionalColourBelow;
     use crate::util;
     use colored::Color;
 
@@ -106,4 +122,27 @@ mod test {
         assert_eq!(util::select_colour_number(9, &cc), Color::Yellow);
         assert_eq!(util::select_colour_number(10, &cc), Color::Red);
     }
+
+    #[test]
+    fn test_select_colour_below() {
+        let cc = ConditionalColourBelow {
+            default_colour: Color::Green,
+            levels: vec![
+                ColouringLevelBelow {
+                    max: 20,
+                    colour: Color::Yellow,
+                },
+                ColouringLevelBelow {
+                    max: 5,
+                    colour: Color::Red,
+                },
+            ],
+        };
+        assert_eq!(util::select_colour_below(100, &cc), Color::Green);
+        assert_eq!(util::select_colour_below(20, &cc), Color::Green);
+        assert_eq!(util::select_colour_below(19, &cc), Color::Yellow);
+        assert_eq!(util::select_colour_below(5, &cc), Color::Yellow);
+        assert_eq!(util::select_colour_below(4, &cc), Color::Red);
+        assert_eq!(util::select_colour_below(0, &cc), Color::Red);
+    }
 }